@@ -0,0 +1,135 @@
+// Compare the cost of inserting variables into the subprogram map through the global
+// `RwLock<HashMap>` (as `dw_tag_variable_handler` does) against an owned `HashMap`
+// threaded through the handlers as a context struct.
+//
+// Run with `cargo bench --bench variable_insertion`.
+
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+// The number of subprograms and the number of variables in each subprogram.
+const SUBPROGRAMS: usize = 10_000;
+const VARIABLES: usize = 50;
+const ROUNDS: usize = 5;
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct Variable {
+    name: String,
+    var_type: usize,
+    location: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+struct Subprogram {
+    variables: Vec<Variable>,
+}
+
+lazy_static! {
+    static ref SUBPROGRAM_MAP: RwLock<HashMap<String, Subprogram>> = RwLock::new(HashMap::new());
+    static ref CURRENT_SUBPROGRAM: RwLock<Option<String>> = RwLock::new(None);
+}
+
+// The owned alternative to the global maps.
+#[derive(Default)]
+struct Context {
+    subprograms: HashMap<String, Subprogram>,
+    current_subprogram: Option<String>,
+}
+
+fn variable(index: usize) -> Variable {
+    Variable {
+        name: format!("var{}", index),
+        var_type: index,
+        location: Some(index as i64 * 8),
+    }
+}
+
+// Mirror the lock acquisitions of `dw_tag_subprogram_handler` and `dw_tag_variable_handler`.
+fn insert_global() {
+    SUBPROGRAM_MAP.write().unwrap().clear();
+    for subprogram in 0..SUBPROGRAMS {
+        let linkage_name = format!("_Z{}", subprogram);
+        SUBPROGRAM_MAP
+            .write()
+            .unwrap()
+            .insert(linkage_name.clone(), Subprogram::default());
+        *CURRENT_SUBPROGRAM.write().unwrap() = Some(linkage_name);
+
+        for index in 0..VARIABLES {
+            let variable = variable(index);
+            let linkage_name = {
+                let current_subprogram = CURRENT_SUBPROGRAM.read().unwrap();
+                match &*current_subprogram {
+                    Some(name) => name.clone(),
+                    None => continue,
+                }
+            };
+            let mut map = SUBPROGRAM_MAP.write().unwrap();
+            if let Some(subprogram) = map.get_mut(&linkage_name) {
+                subprogram.variables.push(variable);
+            }
+        }
+    }
+    black_box(SUBPROGRAM_MAP.read().unwrap().len());
+}
+
+fn insert_owned() {
+    let mut context = Context::default();
+    for subprogram in 0..SUBPROGRAMS {
+        let linkage_name = format!("_Z{}", subprogram);
+        context
+            .subprograms
+            .insert(linkage_name.clone(), Subprogram::default());
+        context.current_subprogram = Some(linkage_name);
+
+        for index in 0..VARIABLES {
+            let variable = variable(index);
+            let Some(linkage_name) = &context.current_subprogram else {
+                continue;
+            };
+            if let Some(subprogram) = context.subprograms.get_mut(linkage_name) {
+                subprogram.variables.push(variable);
+            }
+        }
+    }
+    black_box(context.subprograms.len());
+}
+
+// Run the function several times and report the best round.
+fn bench(name: &str, f: fn()) -> Duration {
+    let best = (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+    let variables = SUBPROGRAMS * VARIABLES;
+    println!(
+        "{:<8} {:>10.2?} ({:.1} ns/variable)",
+        name,
+        best,
+        best.as_nanos() as f64 / variables as f64
+    );
+    best
+}
+
+fn main() {
+    println!(
+        "Inserting {} variables into {} subprograms, best of {} rounds",
+        SUBPROGRAMS * VARIABLES,
+        SUBPROGRAMS,
+        ROUNDS
+    );
+    let global = bench("global", insert_global);
+    let owned = bench("owned", insert_owned);
+    println!(
+        "owned is {:.2}x the speed of global",
+        global.as_secs_f64() / owned.as_secs_f64()
+    );
+}
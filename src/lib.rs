@@ -0,0 +1,4329 @@
+//! A parser for the DWARF debugging information of object files.
+//!
+//! `parse_dwarf` collects the subprograms with their local variables, the types, and the line
+//! number table of every compilation unit into a `ParseOutput`, which can be serialized with serde.
+
+use fallible_iterator::FallibleIterator;
+use gimli::{Endianity as _, Reader as _, Section as _};
+use log::{debug, log_enabled, trace, warn, Level};
+use object::{Object, ObjectSection, ObjectSymbol};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::{borrow, io, mem, path};
+
+/// The version of the output schema, written next to the data so that consumers can reject
+/// incompatible files. It is bumped whenever a serialized field is renamed, removed, or changes meaning.
+pub const SCHEMA_VERSION: u32 = 13;
+
+/// The error returned by `parse_dwarf` and `parse_dwarf_streaming`.
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    /// Reading the input failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// The input is not an object file that `object` can parse, or one of its sections is corrupt.
+    #[error("invalid object file: {0}")]
+    Object(#[from] object::Error),
+    /// The DWARF information is malformed or uses an unsupported form.
+    #[error("invalid DWARF: {0}")]
+    Gimli(#[from] gimli::Error),
+    /// The object file has no DWARF debugging information, e.g. it is stripped.
+    #[error("no DWARF debugging information found")]
+    NoDebugInfo,
+    /// A compressed debug section could not be decompressed, e.g. it uses an unknown compression format.
+    #[error("failed to decompress {section}: {source}")]
+    Decompression {
+        section: String,
+        #[source]
+        source: object::Error,
+    },
+    /// `ParseOptions::unit_index` is not the index of a unit of the file.
+    #[error("no unit at index {index}, the file has {count} units")]
+    UnitIndex { index: usize, count: usize },
+    /// A location expression could not be decoded or evaluated. It is only logged by the parser, and the
+    /// variable is kept without that location, see `Variable::raw_location`, or the member without its
+    /// offset, or the virtual function without its vtable index.
+    #[error("malformed DWARF expression: {0}")]
+    MalformedExpression(#[source] gimli::Error),
+}
+
+// This is a simple wrapper around `object::read::RelocationMap` that implements
+// `gimli::read::Relocate` for use with `gimli::RelocateReader`.
+// You only need this if you are parsing relocatable object files.
+#[derive(Debug, Default)]
+pub struct RelocationMap(object::read::RelocationMap);
+
+impl gimli::read::Relocate for &RelocationMap {
+    fn relocate_address(&self, offset: usize, value: u64) -> gimli::Result<u64> {
+        Ok(self.0.relocate(offset as u64, value))
+    }
+
+    fn relocate_offset(&self, offset: usize, value: usize) -> gimli::Result<usize> {
+        <usize as gimli::ReaderOffset>::from_u64(self.0.relocate(offset as u64, value as u64))
+    }
+}
+
+// The section data that will be stored in `DwarfSections` and `DwarfPackageSections`.
+#[derive(Default)]
+pub struct Section<'data> {
+    data: borrow::Cow<'data, [u8]>,
+    relocations: RelocationMap,
+}
+
+// The struct that represents a local variable in the stack.
+// var_type is a usize that stands for a DW_TAG_type node, as an offset into `.debug_info`.
+// location holds one entry per pc range, since optimized code can move a variable around.
+// optimized_out is true if the location list is empty, i.e. the variable has no location at any pc.
+// type_name is the name of var_type resolved through the type map, e.g. `const char *`.
+// storage_class summarizes the location, and is None if the location could not be classified.
+// decl_file is the source file path of the declaration, resolved from the line program file table. It is
+// not written, since the path can be looked up with decl_file_index in `CompilationUnit::files`.
+// decl_file_index is the raw DW_AT_decl_file index of the declaration, and None if it has none.
+// parameter is true for the DW_TAG_formal_parameter of a subprogram, which come first in its variables.
+// artificial is true for the variables generated by the compiler (DW_AT_artificial), e.g. `this`.
+// raw_location is the hex bytes of the first location expression that could not be evaluated, e.g.
+// `757f9f` for `DW_OP_breg5 -1 DW_OP_stack_value`, and None if every one was.
+// type_tree is var_type expanded with the types it refers to, which is only filled in and written
+// with `ParseOptions::inline_types`.
+// live_range is the pc range in which the variable is in scope, from the lowest to the highest address
+// of the innermost enclosing DW_TAG_lexical_block, or of the subprogram outside of any block,
+// and None for global variables and for scopes without code.
+// die_offset is the `.debug_info` offset of the DIE, as in `Subprogram`.
+#[derive(Debug, serde::Serialize)]
+pub struct Variable {
+    pub name: String,
+    pub var_type: usize,
+    pub type_name: Option<String>,
+    pub location: Vec<VariableLocation>,
+    pub optimized_out: bool,
+    pub storage_class: Option<StorageClass>,
+    #[serde(skip)]
+    pub decl_file: Option<String>,
+    pub decl_file_index: Option<u64>,
+    pub parameter: bool,
+    pub artificial: bool,
+    pub raw_location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_tree: Option<TypeTree>,
+    pub live_range: Option<(u64, u64)>,
+    pub die_offset: usize,
+}
+
+// A type expanded with the types it refers to, so that it can be read without the type map.
+// name is the resolved name of the type as in `Variable::type_name`, e.g. `struct point *`, and
+// kind is None for void and for types that are not in the type map.
+// inner is the expanded DW_AT_type of the type, e.g. the pointee of a pointer or the element of an array.
+// members and parameters are the expanded members of a structure, class, or union type, and the
+// expanded parameters of a subroutine type.
+// truncated is true for a type that is not expanded, because it is already being expanded on the
+// path from the variable, e.g. the `next` pointer of a linked list node, or the path is too deep.
+#[derive(Debug, serde::Serialize)]
+pub struct TypeTree {
+    pub kind: Option<TypeKind>,
+    pub name: Option<String>,
+    pub byte_size: Option<u64>,
+    pub dimensions: Vec<Dimension>,
+    pub inner: Option<Box<TypeTree>>,
+    pub members: Vec<MemberTree>,
+    pub parameters: Vec<TypeTree>,
+    pub truncated: bool,
+}
+
+// A member of an expanded structure, class, or union type, with the fields of `Member`.
+#[derive(Debug, serde::Serialize)]
+pub struct MemberTree {
+    pub name: String,
+    pub offset: Option<u64>,
+    pub bit_size: Option<u64>,
+    pub data_bit_offset: Option<u64>,
+    pub member_type: TypeTree,
+}
+
+// The entries of a variable location, together with the storage class they are summarized to,
+// and the raw bytes of the first expression that could not be evaluated.
+type LocationEntries = (Vec<VariableLocation>, Option<StorageClass>, Option<String>);
+
+// The category of storage a variable lives in, derived from its location expression.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub enum StorageClass {
+    // An offset from the frame base.
+    Stack,
+    // The value lives in a register.
+    Register,
+    // An absolute address.
+    Static,
+    // The value is known, but not stored anywhere.
+    Constant,
+    // The value is the one a register had at the entry of the subprogram.
+    EntryValue,
+    OptimizedOut,
+    ThreadLocal,
+}
+
+// The struct that represents one entry of a variable location.
+// pc_range is None for a single location expression, which is valid over the whole scope.
+// location is where the value lives in that range, and None if it could not be determined.
+#[derive(Debug, serde::Serialize)]
+pub struct VariableLocation {
+    pub pc_range: Option<(u64, u64)>,
+    pub location: Option<Location>,
+}
+
+// Where the value of a variable lives.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub enum Location {
+    // An offset from the frame base of the subprogram, e.g. `DW_OP_fbreg`.
+    Stack(i64),
+    // A DWARF register number, e.g. `DW_OP_reg0`, and its name on the architecture of the object if known.
+    Register {
+        number: u16,
+        name: Option<String>,
+    },
+    // An offset from the value of a register, e.g. a `DW_OP_fbreg` offset from a CFA frame base
+    // resolved to the CFA register at the entry pc of the subprogram.
+    RegisterOffset {
+        number: u16,
+        name: Option<String>,
+        offset: i64,
+    },
+    // An absolute address, e.g. `DW_OP_addr`.
+    Address(u64),
+    // The value itself, computed by `DW_OP_stack_value`.
+    Value(i64),
+    // The bytes of the value itself, given by `DW_OP_implicit_value`.
+    Bytes(Vec<u8>),
+    // The value that a register had at the entry of the subprogram, e.g.
+    // `DW_OP_entry_value(DW_OP_reg5) DW_OP_stack_value`, which a debugger recovers from the caller.
+    EntryValue {
+        number: u16,
+        name: Option<String>,
+    },
+    // The value is not available, e.g. an empty piece.
+    Optimized,
+}
+
+// The struct that represents a function or method.
+// The linkage_name stands for the function name in elf file, and is empty for C functions and other
+// functions without DW_AT_linkage_name. See `subprogram_key` for the key of the subprogram map.
+// containing_type is the qualified name of the class, structure, or union a member function is declared
+// in, e.g. `ns::MyClass` for `ns::MyClass::method`, and None for functions outside of any type.
+// ret_type is the DW_AT_type of the function, and 0 for a function without a return value, and
+// ret_type_name is its name resolved through the type map as in `Variable::type_name`, e.g. `void`.
+// deleted is true for C++ `= delete` functions, which only exist as declarations.
+// declaration is true for DW_AT_declaration DIEs, e.g. member functions declared in a class or
+// external functions that are called but not defined in the unit. They never replace a definition.
+// is_main is true for the entry point of the program, see `mark_main_subprogram`.
+// external is true for functions visible outside of their unit, marked with DW_AT_external, and false
+// for e.g. C `static` functions.
+// source_file is the path of the input file the subprogram was parsed from, which is left empty here
+// for the caller to fill in, e.g. the command line when it merges several input files.
+// ranges holds the pc ranges of the code of the function, from DW_AT_low_pc/DW_AT_high_pc or DW_AT_ranges,
+// e.g. several ranges for a function split into hot and cold parts, and none for an abstract instance.
+// cfa is the rule of the canonical frame address at the entry pc of a function whose DW_AT_frame_base
+// is DW_OP_call_frame_cfa, which its stack variables are located relative to.
+// template_params holds the template parameters of a generic instantiation, e.g. `T = int, N = 3`.
+// virtuality is the DW_AT_virtuality of a C++ member function, e.g. `DW_VIRTUALITY_pure_virtual`,
+// and vtable_index is the index of its entry in the vtable, from DW_AT_vtable_elem_location.
+// inline is the DW_AT_inline of the abstract instance of the function, e.g. `DW_INL_inlined`, and
+// None for functions that were never inlined. An inlined function without ranges has no out-of-line copy.
+// calling_convention is the DW_AT_calling_convention of the function, e.g. `DW_CC_nocall` for a function
+// whose calls do not follow the ABI of the target, or a vendor convention such as `DW_CC_LLVM_Win64`,
+// and None for the normal convention of the target when the attribute is omitted.
+// call_sites holds the calls made by the function that the compiler described, in DIE order.
+// labels holds the name and address of the DW_TAG_label entries of the function, i.e. its goto targets,
+// in DIE order. Labels without an address, e.g. those of an abstract instance, are left out.
+// die_offset is the `.debug_info` offset of the DIE, which refers back to it in the input file, e.g. for
+// `llvm-dwarfdump --debug-info=<offset>`, and is used to report key collisions. The DIEs of
+// `.debug_types` are numbered after the end of `.debug_info`, see `dw_section_offset_handler`.
+// demangled_name is the linkage_name demangled with the scheme of mangling, which is chosen by
+// `ParseOptions::demangle`, and both are None if the linkage name was not demangled.
+// symbol is the entry of the symbol table of the object file for the function, see `resolve_symbols`,
+// which gives its size when the DIE has no DW_AT_high_pc.
+#[derive(Debug, serde::Serialize)]
+pub struct Subprogram {
+    pub name: String,
+    pub linkage_name: String,
+    pub demangled_name: Option<String>,
+    pub mangling: Option<Mangling>,
+    pub containing_type: Option<String>,
+    pub ret_type: usize,
+    pub ret_type_name: Option<String>,
+    pub deleted: bool,
+    pub declaration: bool,
+    pub is_main: bool,
+    pub external: bool,
+    #[serde(skip)]
+    pub decl_file: Option<String>,
+    pub decl_file_index: Option<u64>,
+    pub source_file: String,
+    pub ranges: Vec<(u64, u64)>,
+    pub cfa: Option<Cfa>,
+    pub template_params: Vec<TemplateParam>,
+    pub virtuality: Option<String>,
+    pub vtable_index: Option<u64>,
+    pub inline: Option<String>,
+    pub calling_convention: Option<String>,
+    pub variables: Vec<Variable>,
+    pub inlines: Vec<InlineInstance>,
+    pub call_sites: Vec<CallSite>,
+    pub labels: Vec<(String, u64)>,
+    pub symbol: Option<SymbolInfo>,
+    pub die_offset: usize,
+}
+
+// The entry of the symbol table of the object file that a subprogram is linked to.
+// address and size are those of the symbol, where size is 0 if unknown, and kind is the kind of the
+// symbol as named by `object`, e.g. `Text`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SymbolInfo {
+    pub address: u64,
+    pub size: u64,
+    pub kind: String,
+}
+
+// The defined symbols of the object file, by name.
+type SymbolTable<'data> = HashMap<&'data str, Vec<SymbolInfo>>;
+
+// The mangling scheme of a linkage name.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub enum Mangling {
+    // The legacy `_ZN...17h<hash>E` or the v0 `_R...` mangling of rustc.
+    Rust,
+    // The Itanium C++ ABI mangling, `_Z...`.
+    Cpp,
+}
+
+// The struct that represents a DW_TAG_template_type_parameter or DW_TAG_template_value_parameter.
+// param_type is the type argument of a type parameter, or the type of the value of a value parameter.
+// value is the DW_AT_const_value of a value parameter, interpreted with the signedness and size of
+// param_type, and None for type parameters and for value parameters without a constant, e.g. pointers.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TemplateParam {
+    pub kind: TemplateParamKind,
+    pub name: String,
+    pub param_type: usize,
+    pub value: Option<i128>,
+}
+
+// Whether a template parameter takes a type or a value.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub enum TemplateParamKind {
+    Type,
+    Value,
+}
+
+// The rule of the canonical frame address (CFA) at an address, from the call frame information in
+// `.eh_frame` or `.debug_frame`, i.e. the CFA is the value of the register plus offset.
+// register_name is the name of the register on the architecture of the object if known.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Cfa {
+    pub register: u16,
+    pub register_name: Option<String>,
+    pub offset: i64,
+}
+
+// The struct that represents a function inlined into a subprogram.
+// name is the name of the inlined function, and call_file/call_line is the source location of the call.
+// ranges holds the pc ranges of the inlined code.
+#[derive(Debug, serde::Serialize)]
+pub struct InlineInstance {
+    pub name: String,
+    pub call_file: Option<String>,
+    pub call_line: Option<u64>,
+    pub ranges: Vec<(u64, u64)>,
+}
+
+// The struct that represents a DW_TAG_call_site or DW_TAG_GNU_call_site, i.e. a call in a subprogram.
+// return_pc is the address right after the call instruction, where the callee returns to.
+// origin is the `.debug_info` offset of the DIE of the called subprogram, and target is its key in
+// the subprogram map. Both are None for indirect calls, whose target is only known at run time.
+// tail_call is true for a call in tail position, which does not return to return_pc.
+#[derive(Debug, serde::Serialize)]
+pub struct CallSite {
+    pub return_pc: Option<u64>,
+    pub origin: Option<usize>,
+    pub target: Option<String>,
+    pub tail_call: bool,
+}
+
+// The metadata of a compilation unit.
+// name is the DW_AT_name of the unit, i.e. the path of the primary source file.
+// comp_dir is the DW_AT_comp_dir of the unit, which relative source paths are resolved against.
+// producer is the DW_AT_producer of the unit, i.e. the compiler and version that produced it.
+// language is the DW_AT_language of the unit, e.g. `DW_LANG_Rust`, and language_code is its numeric value.
+// version is the DWARF version of the unit header, e.g. 4 or 5.
+// subprograms and types are the subprograms and types defined in the unit, keyed as in the unit context.
+// globals holds the variables defined outside of any subprogram, i.e. the global and static variables.
+// type_unit is true for a type unit, i.e. a DW_UT_type unit or a unit of `.debug_types`, which only
+// holds the types that the compiler moved out of the compilation units, e.g. with -fdebug-types-section.
+// source_file is the path of the input file the unit was parsed from, which is left empty for the
+// caller to fill in, as in `Subprogram`.
+// offset is the `.debug_info` offset of the unit header, where the offsets of its DIEs start.
+// The units of `.debug_types` are numbered after the end of `.debug_info`, see `dw_section_offset_handler`.
+// files is the file table of the line program, indexed by the decl_file_index of the subprograms and
+// variables, with the paths resolved as for decl_file. File 0 is the primary source file: in DWARF5 it is
+// the first entry of the table, and before DWARF5, where indices are 1-based and 0 means no file, it is
+// the DW_AT_name of the unit. files is empty if the unit has no line program.
+#[derive(Debug, serde::Serialize)]
+pub struct CompilationUnit {
+    pub name: Option<String>,
+    pub comp_dir: Option<String>,
+    pub source_file: String,
+    pub producer: Option<String>,
+    pub language: Option<String>,
+    pub language_code: Option<u16>,
+    pub version: u16,
+    pub type_unit: bool,
+    pub subprograms: BTreeMap<String, Subprogram>,
+    pub types: BTreeMap<usize, Type>,
+    pub globals: Vec<Variable>,
+    pub offset: usize,
+    pub files: Vec<String>,
+}
+
+// The struct that represents a row of the line number table.
+// pc_range is the half-open range of addresses from this row up to the next row of the sequence.
+// file, line, and column are the source location, where line and column are None if unknown.
+// is_stmt marks the rows that are recommended breakpoint locations.
+#[derive(Debug, serde::Serialize)]
+pub struct LineRow {
+    pub pc_range: (u64, u64),
+    pub file: Option<String>,
+    pub line: Option<u64>,
+    pub column: Option<u64>,
+    pub is_stmt: bool,
+}
+
+// The struct that represents a DW_TAG_*_type node in the type map, which is keyed by its `.debug_info` offset.
+// name is empty for unnamed types such as C pointers. Anonymous structures, classes, unions, and
+// enumerations get a placeholder from their declaration, e.g. `(anonymous union at /src/a.c:12)`, or
+// from their offset if it is unknown, e.g. `(anonymous struct @1234)`.
+// inner_type is the DW_AT_type of the node (pointee, element, underlying or aliased type), and 0 for void.
+// An Unspecified type is a DW_TAG_unspecified_type, e.g. `decltype(nullptr)`, and void when it is unnamed.
+// byte_size is the size of the type in bytes, and None if it is unknown (e.g. void or unbounded arrays).
+// alignment is the DW_AT_alignment of the type in bytes, e.g. from `alignas`, and None for the natural alignment.
+// count is the total number of elements of an array type, i.e. the product of its dimensions, and
+// None if a dimension is not a constant.
+// dimensions holds the element count of each DW_TAG_subrange_type child of an array type, outermost
+// first, e.g. [Count(3), Count(4)] for `int[3][4]`.
+// bit_stride is the distance in bits between the elements of an array type, from its DW_AT_byte_stride or
+// DW_AT_bit_stride, e.g. 4 for a packed Ada array of 4-bit elements, and None if it is the element size.
+// strides holds the DW_AT_byte_stride or DW_AT_bit_stride in bits of each dimension of an array type,
+// in the order of dimensions, and None for a dimension whose elements follow each other.
+// encoding is the DW_AT_encoding of base types, e.g. `DW_ATE_signed`, and None for other types.
+// members holds the fields of structure, class, and union types, where the fields of an unnamed member
+// of an anonymous structure or union are promoted into the containing type, at their offset in it.
+// enumerators holds the named values of enumeration types, whose inner_type is the underlying type.
+// parameters holds the parameter types of subroutine types, whose inner_type is the return type,
+// and variadic is true if the parameters end with DW_TAG_unspecified_parameters, i.e. `...`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Type {
+    pub kind: TypeKind,
+    pub name: String,
+    pub inner_type: usize,
+    pub byte_size: Option<u64>,
+    pub alignment: Option<u64>,
+    pub count: Option<u64>,
+    pub dimensions: Vec<Dimension>,
+    pub bit_stride: Option<u64>,
+    pub strides: Vec<Option<u64>>,
+    pub encoding: Option<String>,
+    pub members: Vec<Member>,
+    pub enumerators: Vec<Enumerator>,
+    pub parameters: Vec<usize>,
+    pub variadic: bool,
+}
+
+// The element count of a dimension of an array type.
+// Count is a constant count, from DW_AT_count or from the bounds.
+// Dynamic is a count that is only known at run time, e.g. of a C variable-length array or a Fortran
+// assumed-shape array, whose DW_AT_count or bounds refer to the DIE at this offset, e.g. an artificial
+// variable, or are None for a location expression that computes them.
+// Unbounded is a dimension without a count, e.g. of a flexible array member `int data[]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+pub enum Dimension {
+    Count(u64),
+    Dynamic(Option<usize>),
+    Unbounded,
+}
+
+// The DW_TAG_*_type of a type node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+pub enum TypeKind {
+    Base,
+    Pointer,
+    Reference,
+    RvalueReference,
+    Const,
+    Volatile,
+    Restrict,
+    Typedef,
+    Struct,
+    Class,
+    Union,
+    Enum,
+    Array,
+    Subroutine,
+    Unspecified,
+}
+
+// The struct that represents a DW_TAG_member of a structure, class, or union type.
+// offset is the byte offset of the member from DW_AT_data_member_location, and None for union members.
+// bit_size is the width of a bit-field member in bits, and None for ordinary members.
+// data_bit_offset is the offset of a bit-field member in bits from the start of the containing type,
+// normalized from the legacy DW_AT_bit_offset encoding when needed.
+// alignment is the DW_AT_alignment of the member in bytes, and None for the natural alignment of its type.
+// accessibility is `public`, `protected`, or `private` from DW_AT_accessibility. Without the attribute,
+// it is the C++ default of the containing type, `private` in a class and `public` in a structure or
+// union, and None in the other languages.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Member {
+    pub name: String,
+    pub member_type: usize,
+    pub offset: Option<u64>,
+    pub bit_size: Option<u64>,
+    pub data_bit_offset: Option<u64>,
+    pub alignment: Option<u64>,
+    pub accessibility: Option<String>,
+}
+
+// The struct that represents a DW_TAG_enumerator of an enumeration type.
+// value is the DW_AT_const_value of the enumerator, interpreted with the signedness and size of the
+// underlying type of the enumeration, e.g. -1 rather than 0xffffffff for an `int` enumeration.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Enumerator {
+    pub name: String,
+    pub value: i128,
+}
+
+// The state of parsing a single unit, which is passed to every handler.
+// Units are parsed in parallel, and each one fills its own context that is merged at the end.
+#[derive(Debug, Default)]
+struct UnitContext {
+    // The map that stores the subprogram data.
+    subprograms: BTreeMap<String, Subprogram>,
+    // The depth and key of the subprograms whose children are being visited, where the key is None
+    // for a declaration that was not inserted, whose children belong to no subprogram.
+    subprogram_scopes: Vec<(isize, Option<String>)>,
+    // The variables outside of any subprogram.
+    globals: Vec<Variable>,
+    // The map that stores the type data, keyed by `.debug_info` offset.
+    types: BTreeMap<usize, Type>,
+    // The depth and offset of the structure, union, enumeration, array, and subroutine types whose
+    // children are being visited.
+    type_scopes: Vec<(isize, usize)>,
+    // The depth and name of the namespaces and classes whose children are being visited.
+    name_scopes: Vec<(isize, String)>,
+    // The depth and pc range of the lexical blocks whose children are being visited, where the range
+    // is None for a block without code.
+    block_scopes: Vec<(isize, Option<(u64, u64)>)>,
+    // The qualified names of the subprograms and global variables, keyed by `.debug_info` offset,
+    // for the definitions that refer to a declaration inside a namespace.
+    qualified_names: HashMap<usize, String>,
+    // The containing types of the member functions, keyed by `.debug_info` offset, for the definitions
+    // outside of the class that refer to their declaration inside it.
+    containing_types: HashMap<usize, String>,
+    // The rows of the line number program of the unit.
+    lines: Vec<LineRow>,
+    // The metadata of the unit.
+    unit: Option<CompilationUnit>,
+    // The type units of the file, which are shared by all units.
+    signatures: Arc<TypeSignatures>,
+    // The offsets of the type declarations that refer to their definition in a type unit with
+    // DW_AT_signature, and of that definition, which are resolved once the types of all units are merged.
+    signature_declarations: Vec<(usize, usize)>,
+    // Whether the root DIE of the unit has DW_AT_main_subprogram, i.e. the unit has the entry point of
+    // the program, when the subprogram itself is not marked.
+    main_unit: bool,
+}
+
+// The key of the type that each type unit defines, by the signature that references it.
+type TypeSignatures = HashMap<gimli::DebugTypeSignature, usize>;
+
+impl UnitContext {
+    /// Get the key of the innermost subprogram whose children are being visited.
+    fn current_subprogram(&self) -> Option<&String> {
+        let (_, key) = self.subprogram_scopes.last()?;
+        key.as_ref()
+    }
+}
+
+// The result of parsing all the units of a file.
+#[derive(Debug, Default)]
+pub struct ParseOutput {
+    // The units with their subprograms and types, in unit order, i.e. sorted by offset.
+    pub units: Vec<CompilationUnit>,
+    // The line number table of all units, in unit order.
+    pub lines: Vec<LineRow>,
+}
+
+impl ParseOutput {
+    /// Iterate over the subprograms of all units, in unit order.
+    pub fn subprograms(&self) -> impl Iterator<Item = &Subprogram> {
+        self.units.iter().flat_map(|unit| unit.subprograms.values())
+    }
+
+    /// Get the entry point of the program, if any subprogram is marked as such, see `Subprogram::is_main`.
+    pub fn main_subprogram(&self) -> Option<&Subprogram> {
+        self.subprograms().find(|subprogram| subprogram.is_main)
+    }
+
+    /// Get the subprograms whose name or linkage name is `name`, e.g. all the overloads of a function.
+    pub fn find_subprograms(&self, name: &str) -> Vec<&Subprogram> {
+        self.subprograms()
+            .filter(|subprogram| subprogram.name == name || subprogram.linkage_name == name)
+            .collect()
+    }
+
+    /// Get the type at `offset`, which is in the last unit that starts before it.
+    pub fn find_type(&self, offset: usize) -> Option<&Type> {
+        let index = self.units.partition_point(|unit| unit.offset <= offset);
+        self.units.get(index.checked_sub(1)?)?.types.get(&offset)
+    }
+
+    /// Get the size in bytes of the type at `offset`, including the sizes inherited along type chains.
+    pub fn type_size(&self, offset: usize) -> Option<u64> {
+        self.find_type(offset)?.byte_size
+    }
+
+    /// Merge the types that are structurally identical, e.g. the same `std::string` emitted by many units.
+    /// Each set of identical types is replaced by the one at the lowest offset, which stays in its unit,
+    /// and every type reference of the subprograms, variables, and types is rewritten to it.
+    /// Returns the number of types removed.
+    pub fn dedup_types(&mut self) -> usize {
+        let canonical = canonical_types(&self.units);
+        let canonical_of = |offset: usize| canonical.get(&offset).copied().unwrap_or(offset);
+        let mut removed = 0;
+        for unit in &mut self.units {
+            let before = unit.types.len();
+            unit.types
+                .retain(|&offset, _| canonical_of(offset) == offset);
+            removed += before - unit.types.len();
+
+            for node in unit.types.values_mut() {
+                node.inner_type = canonical_of(node.inner_type);
+                for member in &mut node.members {
+                    member.member_type = canonical_of(member.member_type);
+                }
+                for parameter in &mut node.parameters {
+                    *parameter = canonical_of(*parameter);
+                }
+            }
+            for subprogram in unit.subprograms.values_mut() {
+                subprogram.ret_type = canonical_of(subprogram.ret_type);
+                for param in &mut subprogram.template_params {
+                    param.param_type = canonical_of(param.param_type);
+                }
+                for variable in &mut subprogram.variables {
+                    variable.var_type = canonical_of(variable.var_type);
+                }
+            }
+            for variable in &mut unit.globals {
+                variable.var_type = canonical_of(variable.var_type);
+            }
+        }
+        removed
+    }
+}
+
+/// Get the canonical offset of every type that is identical to a type at a lower offset.
+/// Types are identical if their own attributes are equal, and the types they refer to are identical,
+/// which is found by splitting the types into classes of equal attributes, and then splitting each
+/// class by the classes of the referenced types until no class is split, so cycles are handled.
+fn canonical_types(units: &[CompilationUnit]) -> HashMap<usize, usize> {
+    let nodes: Vec<_> = units.iter().flat_map(|unit| &unit.types).collect();
+    let index: HashMap<usize, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, &(&offset, _))| (offset, index))
+        .collect();
+
+    // The attributes of a type besides its type references.
+    let mut classes = HashMap::new();
+    let mut class: Vec<usize> = nodes
+        .iter()
+        .map(|(_, node)| {
+            let members: Vec<_> = node
+                .members
+                .iter()
+                .map(|member| {
+                    (
+                        &member.name,
+                        member.offset,
+                        member.bit_size,
+                        member.data_bit_offset,
+                        member.alignment,
+                        &member.accessibility,
+                    )
+                })
+                .collect();
+            let enumerators: Vec<_> = node
+                .enumerators
+                .iter()
+                .map(|enumerator| (&enumerator.name, enumerator.value))
+                .collect();
+            let key = (
+                (node.kind, &node.name, node.byte_size, node.alignment),
+                (&node.dimensions, node.bit_stride, &node.strides),
+                (&node.encoding, node.variadic),
+                (members, enumerators, node.parameters.len()),
+            );
+            let next = classes.len();
+            *classes.entry(key).or_insert(next)
+        })
+        .collect();
+
+    // A reference is either the class of a type in the map, or the offset of a type out of the map.
+    let mut count = classes.len();
+    loop {
+        let reference = |offset: usize| match index.get(&offset) {
+            Some(&index) => Ok(class[index]),
+            None => Err(offset),
+        };
+        let mut classes = HashMap::new();
+        let refined: Vec<usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, (_, node))| {
+                let references: Vec<_> = std::iter::once(node.inner_type)
+                    .chain(node.members.iter().map(|member| member.member_type))
+                    .chain(node.parameters.iter().copied())
+                    .map(reference)
+                    .collect();
+                let next = classes.len();
+                *classes.entry((class[index], references)).or_insert(next)
+            })
+            .collect();
+        class = refined;
+        if classes.len() == count {
+            break;
+        }
+        count = classes.len();
+    }
+
+    // The nodes are in offset order, so the first node of a class has its lowest offset.
+    let mut first = HashMap::new();
+    let mut canonical = HashMap::new();
+    for (&(&offset, _), class) in nodes.iter().zip(class) {
+        let canonical_offset = *first.entry(class).or_insert(offset);
+        if canonical_offset != offset {
+            canonical.insert(offset, canonical_offset);
+        }
+    }
+    canonical
+}
+
+// The reader type that will be stored in `Dwarf` and `DwarfPackage`.
+// If you don't need relocations, you can use `gimli::EndianSlice` directly.
+pub type Reader<'data> =
+    gimli::RelocateReader<gimli::EndianSlice<'data, gimli::RunTimeEndian>, &'data RelocationMap>;
+
+/// The default of `ParseOptions::max_depth`, which is far deeper than what compilers emit.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+// The options of the parser, which guard it against untrusted inputs.
+// max_depth is the deepest nesting of DIEs that is parsed, and the longest chain of types that is
+// followed to resolve a type name or to promote anonymous members. Deeper entries are skipped, and
+// deeper types are truncated, with a warning.
+// inline_types expands the type of every variable into `Variable::type_tree`, which makes the output
+// self-contained but much larger.
+// unit_index selects the only unit that is parsed, by its index in `list_units`, and None parses all.
+// progress counts the units that are parsed, for another thread to report, e.g. as a progress bar.
+// demangle selects how the linkage names of the subprograms are demangled into `Subprogram::demangled_name`.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub max_depth: usize,
+    pub inline_types: bool,
+    pub unit_index: Option<usize>,
+    pub progress: Option<Arc<Progress>>,
+    pub demangle: Demangle,
+}
+
+// How the linkage names are demangled.
+// Auto picks the scheme of each name from its prefix and from the DW_AT_language of its unit, see
+// `mangling_scheme`. Rust and Cpp demangle every name with that scheme, and None leaves them mangled.
+// A name that fails to demangle with the chosen scheme is left mangled.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Demangle {
+    #[default]
+    Auto,
+    Rust,
+    Cpp,
+    None,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_depth: DEFAULT_MAX_DEPTH,
+            inline_types: false,
+            unit_index: None,
+            progress: None,
+            demangle: Demangle::Auto,
+        }
+    }
+}
+
+// The progress of parsing, which is shared by all the files parsed with the same options.
+// total is the number of units to parse, which grows as each file is opened, and done is the number
+// of units parsed so far, which grows from the parsing threads.
+#[derive(Debug, Default)]
+pub struct Progress {
+    total: AtomicUsize,
+    done: AtomicUsize,
+}
+
+impl Progress {
+    /// Get the number of units parsed so far, and the number of units to parse.
+    pub fn get(&self) -> (usize, usize) {
+        (
+            self.done.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        )
+    }
+}
+
+// The summary of a unit, which is read without parsing the unit, see `list_units`.
+// index is the position of the unit in the file, which `ParseOptions::unit_index` selects.
+// offset is the offset of the unit header, as in `CompilationUnit`, and die_count is the number of
+// DIEs in the unit. source_file is left empty for the caller to fill in, as in `Subprogram`.
+#[derive(Debug, serde::Serialize)]
+pub struct UnitSummary {
+    pub index: usize,
+    pub offset: usize,
+    pub name: Option<String>,
+    pub producer: Option<String>,
+    pub version: u16,
+    pub type_unit: bool,
+    pub die_count: usize,
+    pub source_file: String,
+}
+
+// The metadata of an object file and of its DWARF information, which is read without parsing the DIEs
+// besides the root DIE of each unit, see `file_info`.
+// format, architecture, and endianness describe the object file, e.g. `Elf`, `X86_64`, and `Little`.
+// sections holds the debugging sections of the file under their name in it, e.g. `__debug_info` in
+// Mach-O, and debug_info is true if it has DWARF units at all, as a stripped binary does not.
+// units is the number of units, and versions holds their distinct DWARF versions, e.g. [4, 5] for a
+// program linked with a prebuilt library.
+// split_dwarf is true if the DIEs are in separate `.dwo` files, i.e. the file has skeleton units or is
+// itself a `.dwo` file, and debug_link is the file named by `.gnu_debuglink`, if any.
+// source_file is left empty for the caller to fill in, as in `Subprogram`.
+#[derive(Debug, serde::Serialize)]
+pub struct FileInfo {
+    pub format: String,
+    pub architecture: String,
+    pub endianness: String,
+    pub debug_info: bool,
+    pub units: usize,
+    pub versions: Vec<u16>,
+    pub split_dwarf: bool,
+    pub sections: Vec<SectionInfo>,
+    pub debug_link: Option<String>,
+    pub source_file: String,
+}
+
+// A debugging section of an object file, with its size in the file and whether it is compressed.
+#[derive(Debug, serde::Serialize)]
+pub struct SectionInfo {
+    pub name: String,
+    pub size: u64,
+    pub compressed: bool,
+}
+
+/// Parse the DWARF information of an object file, e.g. an ELF executable mapped into memory.
+/// A file without any unit, e.g. a stripped binary, fails with `ParseError::NoDebugInfo`.
+pub fn parse_dwarf(data: &[u8]) -> Result<ParseOutput, ParseError> {
+    parse_dwarf_with_options(data, &ParseOptions::default())
+}
+
+/// Parse the DWARF information of an object file with the given options, see `parse_dwarf`.
+pub fn parse_dwarf_with_options(
+    data: &[u8],
+    options: &ParseOptions,
+) -> Result<ParseOutput, ParseError> {
+    let object = object::File::parse(data)?;
+    dump_file(&object, &object, options)
+}
+
+/// Parse the DWARF information of a separate debug file, e.g. the `.debug` file of a stripped binary
+/// found through its `.gnu_debuglink`, with the given options, see `parse_dwarf`.
+/// The call frame information in `.eh_frame` and the architecture are read from the binary `data`.
+pub fn parse_dwarf_with_debug_file(
+    data: &[u8],
+    debug_data: &[u8],
+    options: &ParseOptions,
+) -> Result<ParseOutput, ParseError> {
+    let object = object::File::parse(data)?;
+    let debug_object = object::File::parse(debug_data)?;
+    dump_file(&object, &debug_object, options)
+}
+
+/// Parse the DWARF information of an object file, and pass each unit with its line number table to
+/// `f` in unit order as soon as it is parsed, instead of collecting all the units in memory.
+/// Type sizes and names are only resolved against the types of their own unit.
+/// An error of `f` stops the parsing and is returned as is.
+pub fn parse_dwarf_streaming<F, E>(data: &[u8], f: F) -> Result<(), E>
+where
+    F: FnMut(CompilationUnit, Vec<LineRow>) -> Result<(), E>,
+    E: From<ParseError>,
+{
+    parse_dwarf_streaming_with_options(data, &ParseOptions::default(), f)
+}
+
+/// Parse the DWARF information of an object file unit by unit with the given options,
+/// see `parse_dwarf_streaming`.
+pub fn parse_dwarf_streaming_with_options<F, E>(
+    data: &[u8],
+    options: &ParseOptions,
+    f: F,
+) -> Result<(), E>
+where
+    F: FnMut(CompilationUnit, Vec<LineRow>) -> Result<(), E>,
+    E: From<ParseError>,
+{
+    let object = object::File::parse(data).map_err(ParseError::from)?;
+    dump_file_streaming(&object, &object, options, f)
+}
+
+/// Parse the DWARF information of a separate debug file unit by unit with the given options,
+/// see `parse_dwarf_streaming` and `parse_dwarf_with_debug_file`.
+pub fn parse_dwarf_streaming_with_debug_file<F, E>(
+    data: &[u8],
+    debug_data: &[u8],
+    options: &ParseOptions,
+    f: F,
+) -> Result<(), E>
+where
+    F: FnMut(CompilationUnit, Vec<LineRow>) -> Result<(), E>,
+    E: From<ParseError>,
+{
+    let object = object::File::parse(data).map_err(ParseError::from)?;
+    let debug_object = object::File::parse(debug_data).map_err(ParseError::from)?;
+    dump_file_streaming(&object, &debug_object, options, f)
+}
+
+// The `.gnu_debuglink` section of a stripped binary, which names the separate file that holds its
+// debugging information, e.g. as written by `objcopy --add-gnu-debuglink`.
+// crc is the CRC-32 of the whole debug file, which tells it apart from the debug file of another build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugLink {
+    pub file_name: String,
+    pub crc: u32,
+}
+
+impl DebugLink {
+    /// Check whether `debug_data` is the debug file this link was made for.
+    pub fn matches(&self, debug_data: &[u8]) -> bool {
+        // The CRC-32 of zlib, which is the checksum of `.gnu_debuglink`.
+        let mut crc = !0u32;
+        for &byte in debug_data {
+            crc ^= u32::from(byte);
+            for _ in 0..8 {
+                crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+            }
+        }
+        !crc == self.crc
+    }
+}
+
+/// Get the `.gnu_debuglink` of an object file, if it has one.
+/// The caller looks the file name up, e.g. next to the binary, and checks it with `DebugLink::matches`.
+pub fn debug_link(data: &[u8]) -> Result<Option<DebugLink>, ParseError> {
+    let object = object::File::parse(data)?;
+    Ok(object.gnu_debuglink()?.map(|(file_name, crc)| DebugLink {
+        file_name: String::from_utf8_lossy(file_name).into_owned(),
+        crc,
+    }))
+}
+
+/// List the units of an object file with their name, producer, and number of DIEs, in unit order.
+/// Only the root DIE of each unit is read, and the other DIEs are counted without being parsed.
+pub fn list_units(data: &[u8]) -> Result<Vec<UnitSummary>, ParseError> {
+    let object = object::File::parse(data)?;
+    let dwarf_sections = load_dwarf_sections(&object)?;
+    let dwarf = borrow_dwarf_sections(&dwarf_sections, &object);
+
+    unit_headers(&dwarf)?
+        .into_par_iter()
+        .enumerate()
+        .map(|(index, header)| {
+            let unit = dwarf_unit(&dwarf, header)?;
+            let unit = unit.unit_ref(&dwarf);
+            let mut entries = unit.entries();
+            let mut producer = None;
+            if let Some((_, root)) = entries.next_dfs()? {
+                if let Some(attr) = root.attr_value(gimli::DW_AT_producer)? {
+                    let string = unit.attr_string(attr)?;
+                    producer = Some(string.to_string_lossy()?.into_owned());
+                }
+            }
+            let mut die_count = 1;
+            while entries.next_dfs()?.is_some() {
+                die_count += 1;
+            }
+            Ok(UnitSummary {
+                index,
+                offset: dw_section_offset_handler(&dwarf, unit.header.offset()),
+                name: match &unit.name {
+                    Some(name) => Some(name.to_string_lossy()?.into_owned()),
+                    None => None,
+                },
+                producer,
+                version: unit.header.version(),
+                type_unit: matches!(
+                    unit.header.type_(),
+                    gimli::UnitType::Type { .. } | gimli::UnitType::SplitType { .. }
+                ),
+                die_count,
+                source_file: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Describe an object file and its DWARF information from the section table, the unit headers, and
+/// the root DIEs of the units, without parsing the other DIEs, e.g. to tell why a file yields no units.
+pub fn file_info(data: &[u8]) -> Result<FileInfo, ParseError> {
+    let object = object::File::parse(data)?;
+    let dwarf_sections = load_dwarf_sections(&object)?;
+    let dwarf = borrow_dwarf_sections(&dwarf_sections, &object);
+    let headers = unit_headers(&dwarf)?;
+
+    let mut versions: Vec<_> = headers.iter().map(|header| header.version()).collect();
+    versions.sort_unstable();
+    versions.dedup();
+    let mut sections = Vec::new();
+    for section in object.sections() {
+        let name = section.name()?;
+        let debug = [".debug_", ".zdebug_", "__debug_", "__zdebug_"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix));
+        if debug {
+            let compressed = section.compressed_file_range()?.format
+                != object::CompressionFormat::None
+                || name.contains("zdebug_");
+            sections.push(SectionInfo {
+                name: name.to_string(),
+                size: section.size(),
+                compressed,
+            });
+        }
+    }
+    // Before DWARF5, a skeleton unit is only told apart by the DW_AT_GNU_dwo_name of its root DIE.
+    let mut split_dwarf = sections
+        .iter()
+        .any(|section| section.name.ends_with(".dwo"));
+    for header in &headers {
+        split_dwarf |= match header.type_() {
+            gimli::UnitType::Skeleton(_)
+            | gimli::UnitType::SplitCompilation(_)
+            | gimli::UnitType::SplitType { .. } => true,
+            gimli::UnitType::Compilation => {
+                dwarf_unit(&dwarf, header.clone())?.dwo_name()?.is_some()
+            }
+            _ => false,
+        };
+    }
+
+    Ok(FileInfo {
+        format: format!("{:?}", object.format()),
+        architecture: format!("{:?}", object.architecture()),
+        endianness: format!("{:?}", object.endianness()),
+        debug_info: !headers.is_empty(),
+        units: headers.len(),
+        versions,
+        split_dwarf,
+        sections,
+        debug_link: debug_link(data)?.map(|link| link.file_name),
+        source_file: String::new(),
+    })
+}
+
+/// Get the headers of the units of `.debug_info`, followed by the type units of `.debug_types`.
+fn unit_headers<'a>(
+    dwarf: &gimli::Dwarf<Reader<'a>>,
+) -> Result<Vec<gimli::UnitHeader<Reader<'a>>>, gimli::Error> {
+    let mut headers: Vec<_> = dwarf.units().collect()?;
+    headers.extend(dwarf.type_units().collect::<Vec<_>>()?);
+    Ok(headers)
+}
+
+/// Select the header of the unit at `ParseOptions::unit_index`, or keep all of them.
+fn select_unit_headers<'a>(
+    mut headers: Vec<gimli::UnitHeader<Reader<'a>>>,
+    options: &ParseOptions,
+) -> Result<Vec<gimli::UnitHeader<Reader<'a>>>, ParseError> {
+    let Some(index) = options.unit_index else {
+        return Ok(headers);
+    };
+    if index >= headers.len() {
+        return Err(ParseError::UnitIndex {
+            index,
+            count: headers.len(),
+        });
+    }
+    Ok(vec![headers.swap_remove(index)])
+}
+
+/// Load the DWARF sections of the object file.
+fn load_dwarf_sections<'data>(
+    object: &object::File<'data>,
+) -> Result<gimli::DwarfSections<Section<'data>>, ParseError> {
+    gimli::DwarfSections::load(|id| load_section(object, id.name()))
+}
+
+/// Create `Reader`s for all of the sections and do preliminary parsing.
+/// Alternatively, we could have used `Dwarf::load` with an owned type such as `EndianRcSlice`.
+fn borrow_dwarf_sections<'data>(
+    dwarf_sections: &'data gimli::DwarfSections<Section<'data>>,
+    object: &object::File,
+) -> gimli::Dwarf<Reader<'data>> {
+    let endian = object_endian(object);
+    dwarf_sections.borrow(|section| borrow_section(section, endian))
+}
+
+/// Get the byte order of the object file.
+fn object_endian(object: &object::File) -> gimli::RunTimeEndian {
+    if object.is_little_endian() {
+        gimli::RunTimeEndian::Little
+    } else {
+        gimli::RunTimeEndian::Big
+    }
+}
+
+/// Create a `Reader` for the section data.
+fn borrow_section<'data>(
+    section: &'data Section<'data>,
+    endian: gimli::RunTimeEndian,
+) -> Reader<'data> {
+    let slice = gimli::EndianSlice::new(borrow::Cow::as_ref(&section.data), endian);
+    gimli::RelocateReader::new(slice, &section.relocations)
+}
+
+/// Load a `Section` that may own its data, which is empty if the object file does not have it.
+/// Sections compressed with zlib or zstd, i.e. SHF_COMPRESSED or GNU `.zdebug_*`, are decompressed.
+fn load_section<'data>(
+    object: &object::File<'data>,
+    name: &str,
+) -> Result<Section<'data>, ParseError> {
+    Ok(match object.section_by_name(name) {
+        Some(section) => Section {
+            data: section_data(&section, name)?,
+            relocations: section.relocation_map().map(RelocationMap)?,
+        },
+        None => Default::default(),
+    })
+}
+
+/// Load a call frame information section like `load_section`, but without its relocations if `object`
+/// does not support them. The relocations of `.eh_frame` in a relocatable object file are PC-relative,
+/// e.g. R_X86_64_PC32, which would otherwise fail the whole file although the CFI is only used to
+/// resolve frame bases.
+fn load_frame_section<'data>(
+    object: &object::File<'data>,
+    name: &str,
+) -> Result<Section<'data>, ParseError> {
+    Ok(match object.section_by_name(name) {
+        Some(section) => Section {
+            data: section_data(&section, name)?,
+            relocations: match section.relocation_map() {
+                Ok(relocations) => RelocationMap(relocations),
+                Err(error) => {
+                    warn!(
+                        "failed to read the relocations of {}, ignoring them: {}",
+                        name, error
+                    );
+                    Default::default()
+                }
+            },
+        },
+        None => Default::default(),
+    })
+}
+
+/// Get the uncompressed data of a section.
+fn section_data<'data>(
+    section: &object::Section<'data, '_>,
+    name: &str,
+) -> Result<borrow::Cow<'data, [u8]>, ParseError> {
+    section
+        .uncompressed_data()
+        .map_err(|source| ParseError::Decompression {
+            section: name.to_string(),
+            source,
+        })
+}
+
+// The call frame information sections, `.eh_frame` and `.debug_frame`.
+struct FrameSections<'data> {
+    eh_frame: Section<'data>,
+    debug_frame: Section<'data>,
+}
+
+// The call frame information of the object file, which gives the CFA rule at an address.
+// The FDEs of each section are sorted by their initial address, so that they can be searched.
+struct CallFrameInfo<'data> {
+    eh_frame: gimli::EhFrame<Reader<'data>>,
+    eh_frame_fdes: Vec<gimli::FrameDescriptionEntry<Reader<'data>>>,
+    debug_frame: gimli::DebugFrame<Reader<'data>>,
+    debug_frame_fdes: Vec<gimli::FrameDescriptionEntry<Reader<'data>>>,
+    bases: gimli::BaseAddresses,
+}
+
+/// Load the call frame information sections of the object file.
+/// `.eh_frame` is loaded code and stays in a stripped binary, while `.debug_frame` moves to its
+/// separate debug file, which is the object file itself if there is none.
+fn load_frame_sections<'data>(
+    object: &object::File<'data>,
+    debug_object: &object::File<'data>,
+) -> Result<FrameSections<'data>, ParseError> {
+    Ok(FrameSections {
+        eh_frame: load_frame_section(object, ".eh_frame")?,
+        debug_frame: load_frame_section(debug_object, ".debug_frame")?,
+    })
+}
+
+/// Create `Reader`s for the call frame information sections, and collect their FDEs.
+/// The CFI is only used to resolve frame bases, so an entry that fails to parse ends its section
+/// with a warning instead of failing the whole file.
+fn borrow_frame_sections<'data>(
+    frame_sections: &'data FrameSections<'data>,
+    object: &object::File,
+) -> CallFrameInfo<'data> {
+    let endian = object_endian(object);
+    let address_size = if object.is_64() { 8 } else { 4 };
+    let section_address = |name| {
+        object
+            .section_by_name(name)
+            .map(|section| section.address())
+    };
+    let mut bases = gimli::BaseAddresses::default();
+    if let Some(address) = section_address(".eh_frame") {
+        bases = bases.set_eh_frame(address);
+    }
+    if let Some(address) = section_address(".text") {
+        bases = bases.set_text(address);
+    }
+    if let Some(address) = section_address(".got") {
+        bases = bases.set_got(address);
+    }
+
+    let mut eh_frame = gimli::EhFrame::from(borrow_section(&frame_sections.eh_frame, endian));
+    eh_frame.set_address_size(address_size);
+    let mut debug_frame =
+        gimli::DebugFrame::from(borrow_section(&frame_sections.debug_frame, endian));
+    debug_frame.set_address_size(address_size);
+
+    let eh_frame_fdes = frame_fdes(&eh_frame, &bases);
+    let debug_frame_fdes = frame_fdes(&debug_frame, &bases);
+    CallFrameInfo {
+        eh_frame,
+        eh_frame_fdes,
+        debug_frame,
+        debug_frame_fdes,
+        bases,
+    }
+}
+
+/// Collect the FDEs of a call frame information section, sorted by their initial address.
+fn frame_fdes<'data, S>(
+    section: &S,
+    bases: &gimli::BaseAddresses,
+) -> Vec<gimli::FrameDescriptionEntry<Reader<'data>>>
+where
+    S: gimli::UnwindSection<Reader<'data>>,
+{
+    let mut fdes = Vec::new();
+    let mut entries = section.entries(bases);
+    loop {
+        let fde = match entries.next() {
+            Ok(Some(gimli::CieOrFde::Fde(partial))) => partial.parse(S::cie_from_offset),
+            Ok(Some(gimli::CieOrFde::Cie(_))) => continue,
+            Ok(None) => break,
+            Err(err) => Err(err),
+        };
+        match fde {
+            Ok(fde) => fdes.push(fde),
+            Err(err) => {
+                warn!("Unable to parse the call frame information: {}", err);
+                break;
+            }
+        }
+    }
+    fdes.sort_by_key(|fde| fde.initial_address());
+    fdes
+}
+
+impl CallFrameInfo<'_> {
+    /// Get the CFA rule at `address` from `.eh_frame`, or else from `.debug_frame`.
+    /// None is returned if no FDE covers the address, or if the CFA is given by an expression.
+    fn cfa(&self, address: u64) -> Option<Cfa> {
+        let mut context = gimli::UnwindContext::new();
+        let row = if let Some(fde) = find_fde(&self.eh_frame_fdes, address) {
+            fde.unwind_info_for_address(&self.eh_frame, &self.bases, &mut context, address)
+        } else {
+            let fde = find_fde(&self.debug_frame_fdes, address)?;
+            fde.unwind_info_for_address(&self.debug_frame, &self.bases, &mut context, address)
+        };
+        match row {
+            Ok(row) => match *row.cfa() {
+                gimli::CfaRule::RegisterAndOffset { register, offset } => Some(Cfa {
+                    register: register.0,
+                    register_name: None,
+                    offset,
+                }),
+                gimli::CfaRule::Expression(_) => None,
+            },
+            Err(err) => {
+                debug!("Unable to unwind {:#x}: {}", address, err);
+                None
+            }
+        }
+    }
+}
+
+/// Find the FDE that covers `address` in FDEs sorted by their initial address.
+fn find_fde<'a, 'data>(
+    fdes: &'a [gimli::FrameDescriptionEntry<Reader<'data>>],
+    address: u64,
+) -> Option<&'a gimli::FrameDescriptionEntry<Reader<'data>>> {
+    let index = fdes.partition_point(|fde| fde.initial_address() <= address);
+    let fde = fdes.get(index.checked_sub(1)?)?;
+    fde.contains(address).then_some(fde)
+}
+
+/// Index the types defined by the type units among `headers` by their signature.
+fn type_signatures(
+    dwarf: &gimli::Dwarf<Reader>,
+    headers: &[gimli::UnitHeader<Reader>],
+) -> TypeSignatures {
+    headers
+        .iter()
+        .filter_map(|header| match header.type_() {
+            gimli::UnitType::Type {
+                type_signature,
+                type_offset,
+            }
+            | gimli::UnitType::SplitType {
+                type_signature,
+                type_offset,
+            } => {
+                let unit_offset = dw_section_offset_handler(dwarf, header.offset());
+                Some((type_signature, unit_offset + type_offset.0))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parse the given compilation units in parallel, and return their contexts in unit order.
+fn dump_units<'a>(
+    dwarf: &gimli::Dwarf<Reader<'a>>,
+    cfi: &CallFrameInfo<'a>,
+    signatures: &Arc<TypeSignatures>,
+    options: &ParseOptions,
+    headers: Vec<gimli::UnitHeader<Reader<'a>>>,
+) -> Result<Vec<UnitContext>, ParseError> {
+    headers
+        .into_par_iter()
+        .map(|header| {
+            let unit = dwarf_unit(dwarf, header)?;
+            let unit_ref = unit.unit_ref(dwarf);
+            let context = dump_unit(unit_ref, cfi, signatures, options)?;
+            if let Some(progress) = &options.progress {
+                progress.done.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(context)
+        })
+        .collect()
+}
+
+/// Parse the header and the root DIE of a unit.
+fn dwarf_unit<'a>(
+    dwarf: &gimli::Dwarf<Reader<'a>>,
+    header: gimli::UnitHeader<Reader<'a>>,
+) -> Result<gimli::Unit<Reader<'a>>, gimli::Error> {
+    let mut unit = dwarf.unit(header)?;
+    // gimli loads DW_AT_str_offsets_base from the root DIE. The split units of a `.dwo` file have
+    // none, and index the only contribution of `.debug_str_offsets`, right after its header.
+    if unit.header.version() >= 5 && unit.str_offsets_base.0 == 0 {
+        match unit.header.type_() {
+            gimli::UnitType::SplitCompilation(_) | gimli::UnitType::SplitType { .. } => {
+                unit.str_offsets_base = gimli::DebugStrOffsetsBase::default_for_encoding_and_file(
+                    unit.encoding(),
+                    gimli::DwarfFileType::Dwo,
+                );
+            }
+            _ if !dwarf.debug_str_offsets.reader().is_empty() => warn!(
+                "unit at {:#x} has no DW_AT_str_offsets_base, its DW_FORM_strx strings may not resolve",
+                dw_section_offset_handler(dwarf, unit.header.offset())
+            ),
+            _ => {}
+        }
+    }
+    Ok(unit)
+}
+
+/// Get the DWARF information from the object file, or from its separate debug file.
+/// The compilation units are parsed in parallel, and collected in unit order.
+fn dump_file(
+    object: &object::File,
+    debug_object: &object::File,
+    options: &ParseOptions,
+) -> Result<ParseOutput, ParseError> {
+    let dwarf_sections = load_dwarf_sections(debug_object)?;
+    let dwarf = borrow_dwarf_sections(&dwarf_sections, debug_object);
+    let frame_sections = load_frame_sections(object, debug_object)?;
+    let cfi = borrow_frame_sections(&frame_sections, object);
+
+    // Iterate over the units of `.debug_info`, followed by the type units of `.debug_types`.
+    let headers = unit_headers(&dwarf)?;
+    if headers.is_empty() {
+        return Err(ParseError::NoDebugInfo);
+    }
+    let signatures = Arc::new(type_signatures(&dwarf, &headers));
+    let headers = select_unit_headers(headers, options)?;
+    if let Some(progress) = &options.progress {
+        progress.total.fetch_add(headers.len(), Ordering::Relaxed);
+    }
+    let mut contexts = dump_units(&dwarf, &cfi, &signatures, options, headers)?;
+
+    // Type references may point into other units, so sizes and names are resolved against the types
+    // of all units, which are then split back into their units by offset.
+    let mut types = BTreeMap::new();
+    let mut signature_declarations = Vec::new();
+    for context in &mut contexts {
+        types.append(&mut context.types);
+        signature_declarations.append(&mut context.signature_declarations);
+    }
+    resolve_signature_declarations(&mut types, &signature_declarations);
+    fill_type_sizes(&mut types);
+    normalize_enumerators(&mut types);
+    promote_anonymous_members(&mut types, options.max_depth);
+
+    let architecture = object.architecture();
+    let entry = object_entry(object);
+    let symbols = symbol_table(object, debug_object);
+    let mut parsed = ParseOutput::default();
+    for mut context in contexts {
+        resolve_variables(&mut context, &types, architecture, options);
+        normalize_template_params(&mut context, &types);
+        mark_main_subprogram(&mut context, entry);
+        resolve_symbols(&mut context, &symbols);
+        if let Some(mut unit) = context.unit {
+            unit.subprograms = context.subprograms;
+            unit.globals = context.globals;
+            parsed.units.push(unit);
+        }
+        parsed.lines.extend(context.lines);
+    }
+    for unit in parsed.units.iter_mut().rev() {
+        unit.types = types.split_off(&unit.offset);
+    }
+    Ok(parsed)
+}
+
+/// Get the DWARF information from the object file, or from its separate debug file, and pass each
+/// unit to `f` in unit order.
+/// The compilation units are parsed in parallel batches of one unit per thread, so that only
+/// a batch of units is held in memory at a time.
+fn dump_file_streaming<F, E>(
+    object: &object::File,
+    debug_object: &object::File,
+    options: &ParseOptions,
+    mut f: F,
+) -> Result<(), E>
+where
+    F: FnMut(CompilationUnit, Vec<LineRow>) -> Result<(), E>,
+    E: From<ParseError>,
+{
+    let dwarf_sections = load_dwarf_sections(debug_object)?;
+    let dwarf = borrow_dwarf_sections(&dwarf_sections, debug_object);
+    let frame_sections = load_frame_sections(object, debug_object)?;
+    let cfi = borrow_frame_sections(&frame_sections, object);
+
+    // The type units are indexed up front, as any unit may refer to them before they are parsed.
+    let headers = unit_headers(&dwarf).map_err(ParseError::from)?;
+    if headers.is_empty() {
+        return Err(ParseError::NoDebugInfo.into());
+    }
+    let signatures = Arc::new(type_signatures(&dwarf, &headers));
+    let headers = select_unit_headers(headers, options)?;
+    if let Some(progress) = &options.progress {
+        progress.total.fetch_add(headers.len(), Ordering::Relaxed);
+    }
+
+    let architecture = object.architecture();
+    let entry = object_entry(object);
+    let symbols = symbol_table(object, debug_object);
+    for batch in headers.chunks(rayon::current_num_threads()) {
+        for mut context in dump_units(&dwarf, &cfi, &signatures, options, batch.to_vec())? {
+            fill_type_sizes(&mut context.types);
+            normalize_enumerators(&mut context.types);
+            promote_anonymous_members(&mut context.types, options.max_depth);
+            let types = mem::take(&mut context.types);
+            resolve_variables(&mut context, &types, architecture, options);
+            normalize_template_params(&mut context, &types);
+            mark_main_subprogram(&mut context, entry);
+            resolve_symbols(&mut context, &symbols);
+            if let Some(mut unit) = context.unit {
+                unit.subprograms = context.subprograms;
+                unit.types = types;
+                unit.globals = context.globals;
+                f(unit, context.lines)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replace the type declarations that refer to their definition in a type unit with DW_AT_signature by
+/// a copy of the definition, so that the variables and members of the declared type see its members.
+/// The declarations are usually skeletons, e.g. `struct Point` without members, or the class that
+/// holds the declarations of the member functions defined in the unit.
+fn resolve_signature_declarations(
+    types: &mut BTreeMap<usize, Type>,
+    declarations: &[(usize, usize)],
+) {
+    for &(declaration, definition) in declarations {
+        if let Some(definition) = types.get(&definition).cloned() {
+            types.insert(declaration, definition);
+        }
+    }
+}
+
+/// Fill in the sizes of the types that inherit them along a type chain, see `type_size`.
+fn fill_type_sizes(types: &mut BTreeMap<usize, Type>) {
+    let sizes: Vec<_> = types
+        .keys()
+        .map(|&offset| (offset, type_size(types, offset)))
+        .collect();
+    for (offset, byte_size) in sizes {
+        if let Some(node) = types.get_mut(&offset) {
+            node.byte_size = byte_size;
+        }
+    }
+}
+
+/// Promote the fields of the unnamed members of an anonymous structure or union type into the type
+/// that contains the member, e.g. `union { int i; float f; };` inside a structure, so that the members
+/// of the containing type describe its whole layout. Nested anonymous members are promoted recursively,
+/// up to `max_depth` levels.
+fn promote_anonymous_members(types: &mut BTreeMap<usize, Type>, max_depth: usize) {
+    /// Append the members to `promoted`, replacing each unnamed member of a structure or union type
+    /// with its fields, offset by the offset of the member. `visited` holds the types being expanded.
+    fn promote(
+        types: &BTreeMap<usize, Type>,
+        members: &[Member],
+        visited: &mut HashSet<usize>,
+        max_depth: usize,
+        promoted: &mut Vec<Member>,
+    ) {
+        for member in members {
+            let anonymous = types.get(&member.member_type).filter(|node| {
+                member.name.is_empty()
+                    && matches!(
+                        node.kind,
+                        TypeKind::Struct | TypeKind::Class | TypeKind::Union
+                    )
+            });
+            let anonymous = anonymous.filter(|_| {
+                if visited.len() >= max_depth {
+                    warn!(
+                        "Anonymous members at {:#x} are nested deeper than {}, not promoting them",
+                        member.member_type, max_depth
+                    );
+                    return false;
+                }
+                visited.insert(member.member_type)
+            });
+            let Some(node) = anonymous else {
+                promoted.push(member.clone());
+                continue;
+            };
+            let start = promoted.len();
+            promote(types, &node.members, visited, max_depth, promoted);
+            visited.remove(&member.member_type);
+
+            // Union fields have no offset of their own, and are at the offset of the member.
+            let base = member.offset.unwrap_or(0);
+            for field in &mut promoted[start..] {
+                field.offset = match (member.offset, field.offset) {
+                    (None, None) => None,
+                    (_, offset) => Some(base + offset.unwrap_or(0)),
+                };
+                field.data_bit_offset = field.data_bit_offset.map(|bits| bits + base * 8);
+            }
+        }
+    }
+
+    let expanded: Vec<_> = types
+        .iter()
+        .filter(|(_, node)| node.members.iter().any(|member| member.name.is_empty()))
+        .map(|(&offset, node)| {
+            let mut promoted = Vec::new();
+            promote(
+                types,
+                &node.members,
+                &mut HashSet::from([offset]),
+                max_depth,
+                &mut promoted,
+            );
+            (offset, promoted)
+        })
+        .collect();
+    for (offset, members) in expanded {
+        if let Some(node) = types.get_mut(&offset) {
+            node.members = members;
+        }
+    }
+}
+
+/// Normalize the enumerator values of the enumeration types to the signedness and size of their
+/// underlying type. DW_FORM_data* values are raw bits, e.g. a data4 `0xffffffff` is -1 for an `int`
+/// enumeration, and an sdata -1 is 0xffffffff for an `unsigned int` one.
+/// Enumerations without a known underlying type keep the values as read.
+fn normalize_enumerators(types: &mut BTreeMap<usize, Type>) {
+    let signedness: Vec<_> = types
+        .iter()
+        .filter(|(_, node)| !node.enumerators.is_empty())
+        .filter_map(|(&offset, node)| Some((offset, type_signed(types, node.inner_type)?)))
+        .collect();
+    for (offset, signed) in signedness {
+        let Some(node) = types.get_mut(&offset) else {
+            continue;
+        };
+        let byte_size = node.byte_size;
+        for enumerator in &mut node.enumerators {
+            enumerator.value = normalize_constant(enumerator.value, signed, byte_size);
+        }
+    }
+}
+
+/// Normalize the values of the template value parameters of the unit to their type, as enumerators.
+fn normalize_template_params(context: &mut UnitContext, types: &BTreeMap<usize, Type>) {
+    let params = context
+        .subprograms
+        .values_mut()
+        .flat_map(|subprogram| subprogram.template_params.iter_mut());
+    for param in params {
+        let Some(value) = param.value else {
+            continue;
+        };
+        if let Some(signed) = type_signed(types, param.param_type) {
+            let byte_size = type_size(types, param.param_type);
+            param.value = Some(normalize_constant(value, signed, byte_size));
+        }
+    }
+}
+
+/// Interpret the low `byte_size` bytes of a constant as a signed or unsigned integer.
+/// The constants are 64 bits wide at most, which an i128 holds both signed and unsigned.
+fn normalize_constant(value: i128, signed: bool, byte_size: Option<u64>) -> i128 {
+    let shift = match byte_size {
+        Some(size @ 1..=8) => 64 - size as u32 * 8,
+        _ => 0,
+    };
+    let bits = (value as u64) << shift;
+    if signed {
+        i128::from((bits as i64) >> shift)
+    } else {
+        i128::from(bits >> shift)
+    }
+}
+
+/// Get whether the type at `offset` is a signed integer, following typedefs and qualifiers to the base type.
+/// None is returned if the chain does not end at a base type.
+fn type_signed(types: &BTreeMap<usize, Type>, offset: usize) -> Option<bool> {
+    let mut visited = HashSet::new();
+    let mut offset = offset;
+    loop {
+        if !visited.insert(offset) {
+            return None;
+        }
+        let node = types.get(&offset)?;
+        match node.kind {
+            TypeKind::Typedef | TypeKind::Const | TypeKind::Volatile | TypeKind::Restrict => {}
+            TypeKind::Base => {
+                return Some(matches!(
+                    node.encoding.as_deref(),
+                    Some("DW_ATE_signed" | "DW_ATE_signed_char")
+                ))
+            }
+            _ => return None,
+        }
+        offset = node.inner_type;
+    }
+}
+
+/// Resolve the type names and register names of the local and global variables of the unit, and the
+/// return type names and demangled names of its subprograms.
+/// The stack variables of a subprogram with a CFA are located relative to the CFA register.
+fn resolve_variables(
+    context: &mut UnitContext,
+    types: &BTreeMap<usize, Type>,
+    architecture: object::Architecture,
+    options: &ParseOptions,
+) {
+    let language = context.unit.as_ref().and_then(|unit| unit.language_code);
+    for subprogram in context.subprograms.values_mut() {
+        subprogram.ret_type_name = resolve_type_name(types, subprogram.ret_type, options.max_depth);
+        if let Some(mangling) =
+            mangling_scheme(options.demangle, language, &subprogram.linkage_name)
+        {
+            subprogram.demangled_name = demangle(mangling, &subprogram.linkage_name);
+            subprogram.mangling = subprogram.demangled_name.as_ref().map(|_| mangling);
+        }
+        let Some(cfa) = &mut subprogram.cfa else {
+            continue;
+        };
+        cfa.register_name = register_name(architecture, cfa.register);
+        let entries = subprogram
+            .variables
+            .iter_mut()
+            .flat_map(|variable| variable.location.iter_mut());
+        for entry in entries {
+            if let Some(Location::Stack(offset)) = entry.location {
+                entry.location = Some(Location::RegisterOffset {
+                    number: cfa.register,
+                    name: None,
+                    offset: cfa.offset + offset,
+                });
+            }
+        }
+    }
+
+    let locals = context
+        .subprograms
+        .values_mut()
+        .flat_map(|subprogram| subprogram.variables.iter_mut());
+    for variable in locals.chain(context.globals.iter_mut()) {
+        variable.type_name = resolve_type_name(types, variable.var_type, options.max_depth);
+        if options.inline_types {
+            variable.type_tree = Some(type_tree(
+                types,
+                variable.var_type,
+                &mut HashSet::new(),
+                options.max_depth,
+            ));
+        }
+        for entry in &mut variable.location {
+            if let Some(
+                Location::Register { number, name }
+                | Location::RegisterOffset { number, name, .. }
+                | Location::EntryValue { number, name },
+            ) = &mut entry.location
+            {
+                *name = register_name(architecture, *number);
+            }
+        }
+    }
+}
+
+/// Choose the mangling scheme of a linkage name, where `language` is the DW_AT_language of its unit.
+/// With `Demangle::Auto`, `_R` is the v0 mangling of rustc, and `_ZN` names that end with the hash of
+/// the legacy mangling of rustc, e.g. `_ZN4core3fmt5write17h0123456789abcdefE`, are Rust as well.
+/// Other `_Z` names are C++, unless the unit is Rust, since rustc can leave out the hash.
+fn mangling_scheme(demangle: Demangle, language: Option<u16>, name: &str) -> Option<Mangling> {
+    match demangle {
+        _ if name.is_empty() => None,
+        Demangle::Rust => Some(Mangling::Rust),
+        Demangle::Cpp => Some(Mangling::Cpp),
+        Demangle::None => None,
+        Demangle::Auto if name.starts_with("_R") || is_rust_legacy_hash(name) => {
+            Some(Mangling::Rust)
+        }
+        Demangle::Auto if name.starts_with("_Z") => match language.map(gimli::DwLang) {
+            Some(gimli::DW_LANG_Rust) => Some(Mangling::Rust),
+            _ => Some(Mangling::Cpp),
+        },
+        Demangle::Auto => None,
+    }
+}
+
+/// Check if a `_ZN` name ends with the `17h<16 hex digits>E` hash of the legacy mangling of rustc.
+fn is_rust_legacy_hash(name: &str) -> bool {
+    let Some(path) = name
+        .strip_prefix("_ZN")
+        .and_then(|path| path.strip_suffix('E'))
+    else {
+        return false;
+    };
+    path.len() >= 19
+        && path.is_char_boundary(path.len() - 19)
+        && path[path.len() - 19..]
+            .strip_prefix("17h")
+            .is_some_and(|hash| hash.bytes().all(|byte| byte.is_ascii_hexdigit()))
+}
+
+/// Demangle a linkage name with the scheme, and None if it is not a valid name of that scheme.
+/// The hash of Rust names is left out, as in `std::fmt::write`.
+fn demangle(mangling: Mangling, name: &str) -> Option<String> {
+    match mangling {
+        Mangling::Rust => rustc_demangle::try_demangle(name)
+            .ok()
+            .map(|demangled| format!("{:#}", demangled)),
+        Mangling::Cpp => cpp_demangle::Symbol::new(name)
+            .ok()?
+            .demangle(&Default::default())
+            .ok(),
+    }
+}
+
+/// Get the entry address of the object file, and None if it has none, e.g. a relocatable object file.
+fn object_entry(object: &object::File) -> Option<u64> {
+    Some(object.entry()).filter(|&entry| entry != 0)
+}
+
+/// Collect the defined symbols of the object file, or of its separate debug file if it is stripped.
+fn symbol_table<'data>(
+    object: &object::File<'data>,
+    debug_object: &object::File<'data>,
+) -> SymbolTable<'data> {
+    let symbols = if object.symbols().next().is_some() {
+        object.symbols()
+    } else {
+        debug_object.symbols()
+    };
+    let mut table = SymbolTable::new();
+    for symbol in symbols.filter(|symbol| symbol.is_definition()) {
+        let Ok(name) = symbol.name() else {
+            continue;
+        };
+        table.entry(name).or_default().push(SymbolInfo {
+            address: symbol.address(),
+            size: symbol.size(),
+            kind: format!("{:?}", symbol.kind()),
+        });
+    }
+    table
+}
+
+/// Link the subprograms of the unit to their symbol, by linkage name, or by name for the functions
+/// without one, e.g. C functions. Among the symbols of the same name, e.g. the C `static` functions of
+/// several units, the one at the start of a range of the subprogram is chosen, and a subprogram
+/// without ranges, e.g. one whose DIE lacks DW_AT_high_pc, is only linked to a name with a single symbol.
+fn resolve_symbols(context: &mut UnitContext, symbols: &SymbolTable) {
+    for subprogram in context.subprograms.values_mut() {
+        let name = match subprogram.linkage_name.as_str() {
+            "" => subprogram.name.as_str(),
+            linkage_name => linkage_name,
+        };
+        let Some(candidates) = symbols.get(name) else {
+            continue;
+        };
+        let at_range = candidates.iter().find(|symbol| {
+            subprogram
+                .ranges
+                .iter()
+                .any(|&(low_pc, _)| low_pc == symbol.address)
+        });
+        subprogram.symbol = match (at_range, &candidates[..]) {
+            (Some(symbol), _) => Some(symbol.clone()),
+            (None, [symbol]) if subprogram.ranges.is_empty() => Some(symbol.clone()),
+            _ => None,
+        };
+    }
+}
+
+/// Mark the entry point of the program among the subprograms of the unit, unless one is already marked
+/// with DW_AT_main_subprogram. When the root DIE of the unit has DW_AT_main_subprogram, the entry point
+/// is the subprogram with the DW_CC_program calling convention of Fortran, or else the one named `main`.
+/// Failing that, it is the subprogram whose code starts at the entry address of the object file, which
+/// is usually a `_start` without debugging information, unless the program is linked with `-e main`.
+fn mark_main_subprogram(context: &mut UnitContext, entry: Option<u64>) {
+    let find = |predicate: &dyn Fn(&Subprogram) -> bool| {
+        context
+            .subprograms
+            .iter()
+            .find(|(_, subprogram)| !subprogram.declaration && predicate(subprogram))
+            .map(|(key, _)| key.clone())
+    };
+    if find(&|subprogram| subprogram.is_main).is_some() {
+        return;
+    }
+    let mut main = None;
+    if context.main_unit {
+        main =
+            find(&|subprogram| subprogram.calling_convention.as_deref() == Some("DW_CC_program"))
+                .or_else(|| find(&|subprogram| subprogram.name == "main"));
+    }
+    if let Some(entry) = entry {
+        main = main.or_else(|| {
+            find(&|subprogram| subprogram.ranges.iter().any(|&(low_pc, _)| low_pc == entry))
+        });
+    }
+    if let Some(subprogram) = main.and_then(|key| context.subprograms.get_mut(&key)) {
+        subprogram.is_main = true;
+    }
+}
+
+/// Expand the type at `offset` with the types it refers to, recursively.
+/// `visited` holds the offsets on the current path, and a type that is already on it, or one deeper
+/// than `max_depth`, is truncated to its name, so that recursive types end.
+fn type_tree(
+    types: &BTreeMap<usize, Type>,
+    offset: usize,
+    visited: &mut HashSet<usize>,
+    max_depth: usize,
+) -> TypeTree {
+    let node = types.get(&offset);
+    let mut tree = TypeTree {
+        kind: node.map(|node| node.kind),
+        name: resolve_type_name(types, offset, max_depth),
+        byte_size: node.and_then(|node| node.byte_size),
+        dimensions: node.map(|node| node.dimensions.clone()).unwrap_or_default(),
+        inner: None,
+        members: Vec::new(),
+        parameters: Vec::new(),
+        truncated: false,
+    };
+    let Some(node) = node else {
+        return tree;
+    };
+    if visited.len() >= max_depth || !visited.insert(offset) {
+        tree.truncated = true;
+        return tree;
+    }
+
+    if node.inner_type != 0 {
+        tree.inner = Some(Box::new(type_tree(
+            types,
+            node.inner_type,
+            visited,
+            max_depth,
+        )));
+    }
+    tree.members = node
+        .members
+        .iter()
+        .map(|member| MemberTree {
+            name: member.name.clone(),
+            offset: member.offset,
+            bit_size: member.bit_size,
+            data_bit_offset: member.data_bit_offset,
+            member_type: type_tree(types, member.member_type, visited, max_depth),
+        })
+        .collect();
+    tree.parameters = node
+        .parameters
+        .iter()
+        .map(|&parameter| type_tree(types, parameter, visited, max_depth))
+        .collect();
+    visited.remove(&offset);
+    tree
+}
+
+/// Get the size in bytes of the type at `offset`.
+/// Types without a DW_AT_byte_size inherit it along the chain: typedefs and qualifiers from
+/// the underlying type, and arrays from their element type multiplied by the element count,
+/// or from their strides, see `strided_array_size`.
+fn type_size(types: &BTreeMap<usize, Type>, offset: usize) -> Option<u64> {
+    let mut visited = HashSet::new();
+    let mut offset = offset;
+    let mut count = 1u64;
+    loop {
+        if !visited.insert(offset) {
+            return None;
+        }
+        let node = types.get(&offset)?;
+        if let Some(byte_size) = node.byte_size {
+            return count.checked_mul(byte_size);
+        }
+        match node.kind {
+            TypeKind::Typedef | TypeKind::Const | TypeKind::Volatile | TypeKind::Restrict => {}
+            TypeKind::Array
+                if node.bit_stride.is_some() || node.strides.iter().any(Option::is_some) =>
+            {
+                return count.checked_mul(strided_array_size(types, node)?);
+            }
+            TypeKind::Array => count = count.checked_mul(node.count?)?,
+            _ => return None,
+        }
+        offset = node.inner_type;
+    }
+}
+
+/// Get the size in bytes of an array type with strides, which is laid out from the innermost dimension:
+/// each dimension spans its count times its stride, which is by default the span of the dimension
+/// inside it, and for the innermost one the bit_stride of the array or else the size of its elements.
+/// A span that does not end on a byte boundary, e.g. of a packed array, is rounded up to whole bytes.
+fn strided_array_size(types: &BTreeMap<usize, Type>, node: &Type) -> Option<u64> {
+    let mut span = match node.bit_stride {
+        Some(bit_stride) => bit_stride,
+        None => type_size(types, node.inner_type)?.checked_mul(8)?,
+    };
+    for (dimension, stride) in node.dimensions.iter().zip(&node.strides).rev() {
+        let Dimension::Count(count) = dimension else {
+            return None;
+        };
+        span = count.checked_mul(stride.unwrap_or(span))?;
+    }
+    Some(span.div_ceil(8))
+}
+
+/// Get the name of a DWARF register number on the given architecture, e.g. 6 is `rbp` on x86-64
+/// and 31 is `sp` on AArch64. Names are in lowercase for every architecture.
+/// None is returned for unknown registers and unsupported architectures.
+pub fn register_name(architecture: object::Architecture, register: u16) -> Option<String> {
+    let register = gimli::Register(register);
+    let name = match architecture {
+        object::Architecture::X86_64 | object::Architecture::X86_64_X32 => {
+            gimli::X86_64::register_name(register)
+        }
+        object::Architecture::I386 => gimli::X86::register_name(register),
+        object::Architecture::Aarch64 | object::Architecture::Aarch64_Ilp32 => {
+            gimli::AArch64::register_name(register)
+        }
+        object::Architecture::Arm => gimli::Arm::register_name(register),
+        object::Architecture::Riscv32 | object::Architecture::Riscv64 => {
+            gimli::RiscV::register_name(register)
+        }
+        object::Architecture::LoongArch64 => gimli::LoongArch::register_name(register),
+        object::Architecture::Mips | object::Architecture::Mips64 => {
+            gimli::MIPS::register_name(register)
+        }
+        object::Architecture::PowerPc64 => gimli::PowerPc64::register_name(register),
+        _ => None,
+    };
+    name.map(str::to_ascii_lowercase)
+}
+
+/// Iterate over the Debugging Information Entries (DIEs) in the unit.
+fn dump_unit<'a>(
+    unit: gimli::UnitRef<Reader<'a>>,
+    cfi: &CallFrameInfo<'a>,
+    signatures: &Arc<TypeSignatures>,
+    options: &ParseOptions,
+) -> Result<UnitContext, ParseError> {
+    let mut context = UnitContext {
+        signatures: signatures.clone(),
+        ..Default::default()
+    };
+    let unit_string = |string: &Option<Reader>| -> Result<Option<String>, gimli::Error> {
+        match string {
+            Some(string) => Ok(Some(string.to_string_lossy()?.into_owned())),
+            None => Ok(None),
+        }
+    };
+    context.unit = Some(CompilationUnit {
+        name: unit_string(&unit.name)?,
+        comp_dir: unit_string(&unit.comp_dir)?,
+        source_file: String::new(),
+        producer: None,
+        language: None,
+        language_code: None,
+        version: unit.header.version(),
+        type_unit: matches!(
+            unit.header.type_(),
+            gimli::UnitType::Type { .. } | gimli::UnitType::SplitType { .. }
+        ),
+        subprograms: BTreeMap::new(),
+        types: BTreeMap::new(),
+        globals: Vec::new(),
+        offset: dw_section_offset_handler(unit.dwarf, unit.header.offset()),
+        files: dw_line_files_handler(&unit)?,
+    });
+
+    // Iterate over the Debugging Information Entries (DIEs) in the unit.
+    let mut depth = 0;
+    let mut too_deep = false;
+    let mut entries = unit.entries();
+    while let Some((delta_depth, entry)) = entries.next_dfs()? {
+        depth += delta_depth;
+
+        // The entries nested deeper than the limit are skipped, which also bounds the scope stacks.
+        if depth > options.max_depth as isize {
+            if !too_deep {
+                warn!(
+                    "DIE at {:#x} is nested deeper than {}, skipping the entries below that depth",
+                    dw_offset_handler(&unit, entry.offset()),
+                    options.max_depth
+                );
+                too_deep = true;
+            }
+            continue;
+        }
+
+        debug!("<{}><{}> {}", depth, entry.offset().0, entry.tag());
+
+        // Leave the subprograms, types, and namespaces whose children have all been visited.
+        while let Some(&(scope_depth, _)) = context.subprogram_scopes.last() {
+            if scope_depth < depth {
+                break;
+            }
+            context.subprogram_scopes.pop();
+        }
+        while let Some(&(type_depth, _)) = context.type_scopes.last() {
+            if type_depth < depth {
+                break;
+            }
+            context.type_scopes.pop();
+        }
+        while let Some(&(scope_depth, _)) = context.name_scopes.last() {
+            if scope_depth < depth {
+                break;
+            }
+            context.name_scopes.pop();
+        }
+        while let Some(&(block_depth, _)) = context.block_scopes.last() {
+            if block_depth < depth {
+                break;
+            }
+            context.block_scopes.pop();
+        }
+
+        match entry.tag() {
+            gimli::DW_TAG_compile_unit
+            | gimli::DW_TAG_type_unit
+            | gimli::DW_TAG_partial_unit
+            | gimli::DW_TAG_skeleton_unit => {
+                dw_tag_compile_unit_handler(&mut context, &unit, entry)?
+            }
+            gimli::DW_TAG_subprogram => {
+                dw_tag_subprogram_handler(&mut context, &unit, cfi, entry, depth)?
+            }
+            gimli::DW_TAG_variable => dw_tag_variable_handler(&mut context, &unit, entry)?,
+            gimli::DW_TAG_inlined_subroutine => {
+                dw_tag_inlined_subroutine_handler(&mut context, &unit, entry)?
+            }
+            gimli::DW_TAG_call_site | gimli::DW_TAG_GNU_call_site => {
+                dw_tag_call_site_handler(&mut context, &unit, entry)?
+            }
+            gimli::DW_TAG_lexical_block => {
+                dw_tag_lexical_block_handler(&mut context, &unit, entry, depth)?
+            }
+            gimli::DW_TAG_label => dw_tag_label_handler(&mut context, &unit, entry)?,
+            gimli::DW_TAG_base_type
+            | gimli::DW_TAG_pointer_type
+            | gimli::DW_TAG_reference_type
+            | gimli::DW_TAG_rvalue_reference_type
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_restrict_type
+            | gimli::DW_TAG_typedef
+            | gimli::DW_TAG_structure_type
+            | gimli::DW_TAG_class_type
+            | gimli::DW_TAG_union_type
+            | gimli::DW_TAG_enumeration_type
+            | gimli::DW_TAG_array_type
+            | gimli::DW_TAG_subroutine_type
+            | gimli::DW_TAG_unspecified_type => {
+                dw_tag_type_handler(&mut context, &unit, entry, depth)?
+            }
+            gimli::DW_TAG_namespace => dw_tag_namespace_handler(&mut context, &unit, entry, depth)?,
+            gimli::DW_TAG_member => dw_tag_member_handler(&mut context, &unit, entry)?,
+            gimli::DW_TAG_enumerator => dw_tag_enumerator_handler(&mut context, &unit, entry)?,
+            gimli::DW_TAG_template_type_parameter | gimli::DW_TAG_template_value_parameter => {
+                dw_tag_template_param_handler(&mut context, &unit, entry, depth)?
+            }
+            gimli::DW_TAG_subrange_type => dw_tag_subrange_handler(&mut context, &unit, entry)?,
+            gimli::DW_TAG_formal_parameter | gimli::DW_TAG_unspecified_parameters => {
+                dw_tag_parameter_handler(&mut context, &unit, entry, depth)?
+            }
+            _ => dw_tag_default_handler(&unit, entry)?,
+        }
+    }
+    context.lines = dump_line_program(&unit)?;
+    Ok(context)
+}
+
+/// Run the line number program of the unit, and collect one row per address range.
+/// Each row lasts until the address of the next row, and an end_sequence row closes the last range
+/// of its sequence without starting a new one.
+fn dump_line_program(unit: &gimli::UnitRef<Reader>) -> Result<Vec<LineRow>, gimli::Error> {
+    let program = match &unit.line_program {
+        Some(program) => program.clone(),
+        None => return Ok(Vec::new()),
+    };
+
+    let mut lines = Vec::new();
+    // File paths are resolved once per file index, as most rows share a few files.
+    let mut files: HashMap<u64, Option<String>> = HashMap::new();
+    let mut current: Option<LineRow> = None;
+    let mut rows = program.rows();
+    while let Some((header, row)) = rows.next_row()? {
+        if let Some(mut line) = current.take() {
+            line.pc_range.1 = row.address();
+            lines.push(line);
+        }
+        if row.end_sequence() {
+            continue;
+        }
+
+        let file = match files.get(&row.file_index()) {
+            Some(file) => file.clone(),
+            None => {
+                let file = dw_line_file_handler(unit, header, row.file_index())?;
+                files.insert(row.file_index(), file.clone());
+                file
+            }
+        };
+        let column = match row.column() {
+            gimli::ColumnType::LeftEdge => None,
+            gimli::ColumnType::Column(column) => Some(column.get()),
+        };
+        current = Some(LineRow {
+            pc_range: (row.address(), row.address()),
+            file,
+            line: row.line().map(|line| line.get()),
+            column,
+            is_stmt: row.is_stmt(),
+        });
+    }
+    // A sequence without an end_sequence row is malformed, but its last row is kept as an empty range.
+    lines.extend(current);
+
+    Ok(lines)
+}
+
+/// Handler for DW_TAG_subprogram, which is a function or method.
+/// we are interested in the name, linkage name, and return type of the function,
+/// and the virtuality and vtable index of a C++ member function.
+fn dw_tag_subprogram_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    cfi: &CallFrameInfo<'a>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+    depth: isize,
+) -> Result<(), gimli::Error> {
+    let mut name = String::new();
+    let mut linkage_name = String::new();
+    let mut ret_type = 0;
+    let mut deleted = false;
+    let mut declaration = false;
+    let mut is_main = false;
+    let mut external = false;
+    let mut decl_file = None;
+    let mut decl_file_index = None;
+    let mut frame_base_cfa = false;
+    let mut virtuality = None;
+    let mut vtable_index = None;
+    let mut inline = None;
+    let mut calling_convention = None;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_name => {
+                name = dw_at_name_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), name);
+            }
+            gimli::DW_AT_abstract_origin | gimli::DW_AT_specification => {
+                trace!(
+                    "   {}: {:?}",
+                    attr.name(),
+                    dw_at_origin_handler(unit, &attr)
+                );
+            }
+            gimli::DW_AT_linkage_name => {
+                linkage_name = dw_at_name_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), linkage_name);
+            }
+            gimli::DW_AT_type => {
+                ret_type = dw_at_type_handler(context, unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), ret_type);
+            }
+            gimli::DW_AT_deleted => {
+                deleted = dw_at_flag_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), deleted);
+            }
+            gimli::DW_AT_declaration => {
+                declaration = dw_at_flag_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), declaration);
+            }
+            gimli::DW_AT_main_subprogram => {
+                is_main = dw_at_flag_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), is_main);
+            }
+            gimli::DW_AT_external => {
+                external = dw_at_flag_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), external);
+            }
+            gimli::DW_AT_decl_file => {
+                decl_file = dw_at_file_handler(unit, &attr)?;
+                decl_file_index = dw_at_file_index_handler(unit, &attr);
+                trace!("   {}: {:?}", attr.name(), decl_file);
+            }
+            gimli::DW_AT_frame_base => {
+                frame_base_cfa = dw_at_frame_base_handler(unit, &attr)?;
+                trace!(
+                    "   {}: DW_OP_call_frame_cfa {:?}",
+                    attr.name(),
+                    frame_base_cfa
+                );
+            }
+            gimli::DW_AT_virtuality => {
+                if let gimli::AttributeValue::Virtuality(value) = attr.value() {
+                    virtuality = Some(value.to_string());
+                }
+                trace!("   {}: {:?}", attr.name(), virtuality);
+            }
+            gimli::DW_AT_vtable_elem_location => {
+                vtable_index = dw_at_vtable_elem_location_handler(unit, &attr);
+                trace!("   {}: {:?}", attr.name(), vtable_index);
+            }
+            gimli::DW_AT_inline => {
+                if let gimli::AttributeValue::Inline(value) = attr.value() {
+                    inline = Some(value.to_string());
+                }
+                trace!("   {}: {:?}", attr.name(), inline);
+            }
+            gimli::DW_AT_calling_convention => {
+                calling_convention = dw_at_calling_convention_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), calling_convention);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    let has_name = !name.is_empty();
+
+    // Inlined and optimized subprograms point at an abstract instance that holds the attributes they lack,
+    // and out-of-line member function definitions point at their declaration inside the class.
+    dw_at_origin_chain_handler(unit, entry, |attr| {
+        match attr.name() {
+            gimli::DW_AT_name if name.is_empty() => {
+                name = dw_at_name_handler(unit, attr)?;
+            }
+            gimli::DW_AT_linkage_name if linkage_name.is_empty() => {
+                linkage_name = dw_at_name_handler(unit, attr)?;
+            }
+            gimli::DW_AT_type if ret_type == 0 => {
+                ret_type = dw_at_type_handler(context, unit, attr)?;
+            }
+            gimli::DW_AT_decl_file if decl_file.is_none() => {
+                decl_file = dw_at_file_handler(unit, attr)?;
+                decl_file_index = dw_at_file_index_handler(unit, attr);
+            }
+            gimli::DW_AT_external if !external => {
+                external = dw_at_flag_handler(attr);
+            }
+            // The definition of a virtual member function leaves these to its declaration in the class.
+            gimli::DW_AT_virtuality if virtuality.is_none() => {
+                if let gimli::AttributeValue::Virtuality(value) = attr.value() {
+                    virtuality = Some(value.to_string());
+                }
+            }
+            gimli::DW_AT_vtable_elem_location if vtable_index.is_none() => {
+                vtable_index = dw_at_vtable_elem_location_handler(unit, attr);
+            }
+            // The out-of-line copy of an inlined function is a concrete instance of the abstract one.
+            gimli::DW_AT_inline if inline.is_none() => {
+                if let gimli::AttributeValue::Inline(value) = attr.value() {
+                    inline = Some(value.to_string());
+                }
+            }
+            gimli::DW_AT_calling_convention if calling_convention.is_none() => {
+                calling_convention = dw_at_calling_convention_handler(attr);
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+
+    // A name of its own is qualified by the enclosing namespaces, while a name from the origin chain
+    // takes the qualified name of the origin, which may be in another namespace than the definition.
+    if has_name {
+        name = scoped_name(context, &name);
+    } else if let Some(qualified) = dw_origin_qualified_name(context, unit, entry)? {
+        name = qualified;
+    }
+    if !name.is_empty() {
+        let offset = dw_offset_handler(unit, entry.offset());
+        context.qualified_names.insert(offset, name.clone());
+    }
+
+    // A member function declared in a class is a direct child of it, while its definition outside of
+    // the class takes the containing type of its declaration.
+    let containing_type = match context.type_scopes.last() {
+        Some(&(type_depth, offset)) if type_depth + 1 == depth => context
+            .types
+            .get(&offset)
+            .filter(|node| {
+                matches!(
+                    node.kind,
+                    TypeKind::Struct | TypeKind::Class | TypeKind::Union
+                )
+            })
+            .map(|node| node.name.clone()),
+        _ => dw_origin_lookup(&context.containing_types, unit, entry)?,
+    };
+    if let Some(containing_type) = &containing_type {
+        let offset = dw_offset_handler(unit, entry.offset());
+        context
+            .containing_types
+            .insert(offset, containing_type.clone());
+    }
+
+    // DW_AT_low_pc/DW_AT_high_pc and DW_AT_ranges are both handled by gimli.
+    // DW_AT_high_pc is the end address in the address class (DW_FORM_addr, DW_FORM_addrx), but the
+    // size of the range from DW_AT_low_pc in the constant class (DW_FORM_data*, DW_FORM_udata,
+    // DW_FORM_sdata), which gimli reads as Udata and adds to the low pc.
+    let ranges: Vec<_> = unit
+        .die_ranges(entry)?
+        .map(|range| Ok((range.begin, range.end)))
+        .collect()?;
+    trace!("   {}: {:x?}", gimli::DW_AT_ranges, ranges);
+
+    // The frame base is the CFA, whose rule at the entry pc is looked up in the call frame information.
+    let cfa = match ranges.first() {
+        Some(&(entry_pc, _)) if frame_base_cfa => cfi.cfa(entry_pc),
+        _ => None,
+    };
+    trace!("   {}: {:?}", gimli::DW_AT_frame_base, cfa);
+
+    // Insert the subprogram data into the map.
+    let die_offset = dw_offset_handler(unit, entry.offset());
+    let key = subprogram_key(&linkage_name, &name, die_offset);
+    let inserted = insert_subprogram(
+        &mut context.subprograms,
+        key.clone(),
+        Subprogram {
+            name,
+            linkage_name,
+            demangled_name: None,
+            mangling: None,
+            containing_type,
+            ret_type,
+            ret_type_name: None,
+            deleted,
+            declaration,
+            is_main,
+            external,
+            decl_file,
+            decl_file_index,
+            source_file: String::new(),
+            ranges,
+            cfa,
+            template_params: Vec::new(),
+            virtuality,
+            vtable_index,
+            inline,
+            calling_convention,
+            variables: Vec::new(),
+            inlines: Vec::new(),
+            call_sites: Vec::new(),
+            labels: Vec::new(),
+            symbol: None,
+            die_offset,
+        },
+    );
+
+    // Enter the subprogram, the children of a declaration that was not inserted belong to no subprogram.
+    context
+        .subprogram_scopes
+        .push((depth, inserted.then_some(key)));
+
+    Ok(())
+}
+
+/// Get the key of a subprogram in the subprogram map.
+/// The key is the linkage name, which is unique for C++ and Rust functions. Functions without one,
+/// e.g. C functions, are keyed by their name, and unnamed ones by the `.debug_info` offset of the DIE.
+fn subprogram_key(linkage_name: &str, name: &str, offset: usize) -> String {
+    if !linkage_name.is_empty() {
+        linkage_name.to_string()
+    } else if !name.is_empty() {
+        name.to_string()
+    } else {
+        format!("{:#x}", offset)
+    }
+}
+
+/// Insert a subprogram into the map, unless it is a declaration and the map already has an entry
+/// for the key, so that a declaration never overwrites a definition.
+/// Two definitions with the same key are compatible if they have the same linkage name, or, without
+/// one, the same name and declaration file, e.g. an abstract instance and its concrete instance,
+/// and the later one replaces the earlier one unless only the earlier one has code.
+/// Otherwise the collision is reported with both DIE offsets and the first definition is kept.
+/// Returns whether the subprogram was inserted.
+fn insert_subprogram(
+    subprograms: &mut BTreeMap<String, Subprogram>,
+    key: String,
+    subprogram: Subprogram,
+) -> bool {
+    let existing = match subprograms.get_mut(&key) {
+        Some(existing) => existing,
+        None => {
+            subprograms.insert(key, subprogram);
+            return true;
+        }
+    };
+    if subprogram.declaration {
+        return false;
+    }
+    let compatible = if subprogram.linkage_name.is_empty() {
+        existing.name == subprogram.name && existing.decl_file == subprogram.decl_file
+    } else {
+        existing.linkage_name == subprogram.linkage_name
+    };
+    if !existing.declaration && !compatible {
+        warn!(
+            "Subprogram key collision on {:?}: keeping the DIE at {:#x}, dropping the DIE at {:#x}",
+            key, existing.die_offset, subprogram.die_offset
+        );
+        return false;
+    }
+
+    // The later definition is usually the more concrete one, e.g. the concrete instance after the abstract one,
+    // but an abstract instance without code never replaces a concrete instance that comes before it.
+    if subprogram.ranges.is_empty() && !existing.ranges.is_empty() {
+        return false;
+    }
+    // Concrete instances leave the template parameters to their abstract instance, so they are kept,
+    // and so are the static locals, which have a single address that the concrete instances do not repeat.
+    let template_params = mem::take(&mut existing.template_params);
+    let statics: Vec<_> = mem::take(&mut existing.variables)
+        .into_iter()
+        .filter(|variable| variable.storage_class == Some(StorageClass::Static))
+        .collect();
+    *existing = subprogram;
+    if compatible {
+        existing.template_params = template_params;
+        existing.variables = statics;
+    }
+    true
+}
+
+/// Handler for DW_TAG_variable and DW_TAG_formal_parameter, which is a local variable or a parameter,
+/// or a global variable outside of subprograms.
+/// we are interested in the name, type, and location(stack offset or address) of the variable.
+fn dw_tag_variable_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<(), ParseError> {
+    let mut name = String::new();
+    let mut var_type = 0;
+    let mut location = Vec::new();
+    let mut optimized_out = false;
+    let mut storage_class = None;
+    let mut raw_location = None;
+    let mut decl_file = None;
+    let mut decl_file_index = None;
+    let mut declaration = false;
+    let mut artificial = false;
+    let parameter = entry.tag() == gimli::DW_TAG_formal_parameter;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_name => {
+                name = dw_at_name_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), name);
+            }
+            gimli::DW_AT_type => {
+                var_type = dw_at_type_handler(context, unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), var_type);
+            }
+            gimli::DW_AT_location => {
+                if let Some((entries, class, raw)) = dw_at_location_handler(unit, &attr)? {
+                    optimized_out = entries.is_empty();
+                    location = entries;
+                    storage_class = class;
+                    raw_location = raw;
+                }
+            }
+            // The variable is optimized into a constant, and has no location at all.
+            gimli::DW_AT_const_value => {
+                storage_class = Some(StorageClass::Constant);
+                trace!("   {}: {:?}", attr.name(), attr.value());
+            }
+            gimli::DW_AT_decl_file => {
+                decl_file = dw_at_file_handler(unit, &attr)?;
+                decl_file_index = dw_at_file_index_handler(unit, &attr);
+                trace!("   {}: {:?}", attr.name(), decl_file);
+            }
+            gimli::DW_AT_declaration => {
+                declaration = dw_at_flag_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), declaration);
+            }
+            gimli::DW_AT_artificial => {
+                artificial = dw_at_flag_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), artificial);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    let has_name = !name.is_empty();
+
+    // The definition of a static data member only points at its declaration inside the class,
+    // and concrete instances of inlined variables point at their abstract instance.
+    dw_at_origin_chain_handler(unit, entry, |attr| {
+        match attr.name() {
+            gimli::DW_AT_name if name.is_empty() => {
+                name = dw_at_name_handler(unit, attr)?;
+            }
+            gimli::DW_AT_type if var_type == 0 => {
+                var_type = dw_at_type_handler(context, unit, attr)?;
+            }
+            gimli::DW_AT_decl_file if decl_file.is_none() => {
+                decl_file = dw_at_file_handler(unit, attr)?;
+                decl_file_index = dw_at_file_index_handler(unit, attr);
+            }
+            gimli::DW_AT_artificial if !artificial => {
+                artificial = dw_at_flag_handler(attr);
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+
+    let mut variable = Variable {
+        name,
+        var_type,
+        type_name: None,
+        location,
+        optimized_out,
+        storage_class,
+        decl_file,
+        decl_file_index,
+        parameter,
+        artificial,
+        raw_location,
+        type_tree: None,
+        live_range: None,
+        die_offset: dw_offset_handler(unit, entry.offset()),
+    };
+
+    // The current subprogram is the key in the subprogram map.
+    // Outside of any subprogram, the variable is a global variable, named after its namespaces.
+    if context.subprogram_scopes.is_empty() && !parameter {
+        if has_name {
+            variable.name = scoped_name(context, &variable.name);
+        } else if let Some(qualified) = dw_origin_qualified_name(context, unit, entry)? {
+            variable.name = qualified;
+        }
+        if !variable.name.is_empty() {
+            let offset = dw_offset_handler(unit, entry.offset());
+            context
+                .qualified_names
+                .insert(offset, variable.name.clone());
+        }
+        // Declarations, e.g. `extern` variables and static data members, have a definition elsewhere.
+        if !declaration {
+            context.globals.push(variable);
+        }
+        return Ok(());
+    }
+    let key = match context.current_subprogram() {
+        Some(key) => key.clone(),
+        None => {
+            return Ok(());
+        }
+    };
+
+    // The variable is in scope in the innermost block of the subprogram with code, or else in the whole
+    // subprogram. The blocks around a nested subprogram, e.g. a GNU C nested function, are not its own.
+    let subprogram_depth = context
+        .subprogram_scopes
+        .last()
+        .map_or(0, |&(depth, _)| depth);
+    let block_range = context
+        .block_scopes
+        .iter()
+        .rev()
+        .take_while(|&&(block_depth, _)| block_depth > subprogram_depth)
+        .find_map(|&(_, range)| range);
+    if let Some(subprogram) = context.subprograms.get_mut(&key) {
+        variable.live_range = block_range.or_else(|| pc_bounds(&subprogram.ranges));
+        subprogram.variables.push(variable);
+    }
+
+    Ok(())
+}
+
+/// Handler for DW_TAG_lexical_block, which is a nested scope of a subprogram, e.g. a `{ ... }` block.
+/// we are interested in the pc range of the block, which is the live range of the variables in it.
+fn dw_tag_lexical_block_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+    depth: isize,
+) -> Result<(), gimli::Error> {
+    // DW_AT_low_pc/DW_AT_high_pc and DW_AT_ranges are both handled by gimli.
+    let ranges: Vec<_> = unit
+        .die_ranges(entry)?
+        .map(|range| Ok((range.begin, range.end)))
+        .collect()?;
+    trace!("   {}: {:x?}", gimli::DW_AT_ranges, ranges);
+
+    // Enter the block, whose variables follow as its children.
+    context.block_scopes.push((depth, pc_bounds(&ranges)));
+
+    Ok(())
+}
+
+/// Get the range from the lowest to the highest address of a list of pc ranges, if any.
+fn pc_bounds(ranges: &[(u64, u64)]) -> Option<(u64, u64)> {
+    let begin = ranges.iter().map(|&(begin, _)| begin).min()?;
+    let end = ranges.iter().map(|&(_, end)| end).max()?;
+    Some((begin, end))
+}
+
+/// Handler for DW_TAG_inlined_subroutine, which is a function inlined at a call site.
+/// we are interested in the inlined function name, the call site, and the pc ranges of the inlined code.
+fn dw_tag_inlined_subroutine_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<(), gimli::Error> {
+    let mut name = String::new();
+    let mut call_file = None;
+    let mut call_line = None;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_abstract_origin => {
+                trace!(
+                    "   {}: {:?}",
+                    attr.name(),
+                    dw_at_origin_handler(unit, &attr)
+                );
+            }
+            gimli::DW_AT_call_file => {
+                call_file = dw_at_file_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), call_file);
+            }
+            gimli::DW_AT_call_line => {
+                call_line = attr.udata_value();
+                trace!("   {}: {:?}", attr.name(), call_line);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    // The name of the inlined function lives in its abstract instance.
+    dw_at_origin_chain_handler(unit, entry, |attr| {
+        if attr.name() == gimli::DW_AT_name && name.is_empty() {
+            name = dw_at_name_handler(unit, attr)?;
+        }
+        Ok(())
+    })?;
+    if let Some(qualified) = dw_origin_qualified_name(context, unit, entry)? {
+        name = qualified;
+    }
+
+    // DW_AT_low_pc/DW_AT_high_pc and DW_AT_ranges are both handled by gimli.
+    let ranges: Vec<_> = unit
+        .die_ranges(entry)?
+        .map(|range| Ok((range.begin, range.end)))
+        .collect()?;
+    trace!("   {}: {:x?}", gimli::DW_AT_ranges, ranges);
+
+    // Attach the inline instance to the subprogram it is inlined into.
+    let key = match context.current_subprogram() {
+        Some(key) => key.clone(),
+        None => {
+            return Ok(());
+        }
+    };
+    if let Some(subprogram) = context.subprograms.get_mut(&key) {
+        subprogram.inlines.push(InlineInstance {
+            name,
+            call_file,
+            call_line,
+            ranges,
+        });
+    }
+
+    Ok(())
+}
+
+/// Handler for DW_TAG_call_site and its GNU extension DW_TAG_GNU_call_site, which is a call made by
+/// the enclosing subprogram.
+/// we are interested in the return address, and the subprogram that is called.
+fn dw_tag_call_site_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<(), gimli::Error> {
+    let mut return_pc = None;
+    let mut origin = None;
+    let mut tail_call = false;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            // The GNU extension gives the return address as DW_AT_low_pc.
+            gimli::DW_AT_call_return_pc | gimli::DW_AT_low_pc => {
+                return_pc = unit.attr_address(attr.value())?;
+                trace!("   {}: {:x?}", attr.name(), return_pc);
+            }
+            // The GNU extension gives the called subprogram as DW_AT_abstract_origin.
+            gimli::DW_AT_call_origin | gimli::DW_AT_abstract_origin => {
+                origin = dw_at_origin_handler(unit, &attr);
+                trace!("   {}: {:?}", attr.name(), origin);
+            }
+            gimli::DW_AT_call_tail_call | gimli::DW_AT_GNU_tail_call => {
+                tail_call = dw_at_flag_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), tail_call);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    // The called subprogram is usually a declaration, which is keyed like its definition.
+    let target = match origin {
+        Some(origin) => {
+            let callee = unit.entry(origin)?;
+            let mut name = String::new();
+            let mut linkage_name = String::new();
+            let mut attrs = callee.attrs();
+            while let Some(attr) = attrs.next()? {
+                match attr.name() {
+                    gimli::DW_AT_name => name = dw_at_name_handler(unit, &attr)?,
+                    gimli::DW_AT_linkage_name => linkage_name = dw_at_name_handler(unit, &attr)?,
+                    _ => {}
+                }
+            }
+            dw_at_origin_chain_handler(unit, &callee, |attr| {
+                match attr.name() {
+                    gimli::DW_AT_name if name.is_empty() => {
+                        name = dw_at_name_handler(unit, attr)?;
+                    }
+                    gimli::DW_AT_linkage_name if linkage_name.is_empty() => {
+                        linkage_name = dw_at_name_handler(unit, attr)?;
+                    }
+                    _ => {}
+                }
+                Ok(())
+            })?;
+            let offset = dw_offset_handler(unit, origin);
+            // The subprograms without a linkage name are keyed by their qualified name.
+            let name = context.qualified_names.get(&offset).unwrap_or(&name);
+            Some(subprogram_key(&linkage_name, name, offset))
+        }
+        None => None,
+    };
+
+    // Attach the call site to the subprogram that makes the call.
+    let key = match context.current_subprogram() {
+        Some(key) => key.clone(),
+        None => {
+            return Ok(());
+        }
+    };
+    if let Some(subprogram) = context.subprograms.get_mut(&key) {
+        subprogram.call_sites.push(CallSite {
+            return_pc,
+            origin: origin.map(|origin| dw_offset_handler(unit, origin)),
+            target,
+            tail_call,
+        });
+    }
+
+    Ok(())
+}
+
+/// Handler for DW_TAG_label, which is a goto target in the enclosing subprogram.
+/// we are interested in the name and the address of the label.
+fn dw_tag_label_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<(), gimli::Error> {
+    let mut name = String::new();
+    let mut low_pc = None;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_name => {
+                name = dw_at_name_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), name);
+            }
+            gimli::DW_AT_low_pc => {
+                low_pc = unit.attr_address(attr.value())?;
+                trace!("   {}: {:x?}", attr.name(), low_pc);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+    // The labels of an inlined copy of a function refer to those of its abstract instance for the name.
+    dw_at_origin_chain_handler(unit, entry, |attr| {
+        if attr.name() == gimli::DW_AT_name && name.is_empty() {
+            name = dw_at_name_handler(unit, attr)?;
+        }
+        Ok(())
+    })?;
+
+    // Attach the label to the subprogram it is in.
+    let Some(low_pc) = low_pc else {
+        return Ok(());
+    };
+    let key = match context.current_subprogram() {
+        Some(key) => key.clone(),
+        None => {
+            return Ok(());
+        }
+    };
+    if let Some(subprogram) = context.subprograms.get_mut(&key) {
+        subprogram.labels.push((name, low_pc));
+    }
+
+    Ok(())
+}
+
+/// Handler for the DW_TAG_*_type DIEs, which are stored in the type map.
+/// we are interested in the name and the referenced type of the node.
+fn dw_tag_type_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+    depth: isize,
+) -> Result<(), gimli::Error> {
+    let kind = match entry.tag() {
+        gimli::DW_TAG_base_type => TypeKind::Base,
+        gimli::DW_TAG_pointer_type => TypeKind::Pointer,
+        gimli::DW_TAG_reference_type => TypeKind::Reference,
+        gimli::DW_TAG_rvalue_reference_type => TypeKind::RvalueReference,
+        gimli::DW_TAG_const_type => TypeKind::Const,
+        gimli::DW_TAG_volatile_type => TypeKind::Volatile,
+        gimli::DW_TAG_restrict_type => TypeKind::Restrict,
+        gimli::DW_TAG_typedef => TypeKind::Typedef,
+        gimli::DW_TAG_structure_type => TypeKind::Struct,
+        gimli::DW_TAG_class_type => TypeKind::Class,
+        gimli::DW_TAG_union_type => TypeKind::Union,
+        gimli::DW_TAG_enumeration_type => TypeKind::Enum,
+        gimli::DW_TAG_array_type => TypeKind::Array,
+        gimli::DW_TAG_subroutine_type => TypeKind::Subroutine,
+        gimli::DW_TAG_unspecified_type => TypeKind::Unspecified,
+        _ => return dw_tag_default_handler(unit, entry),
+    };
+    let mut name = String::new();
+    let mut inner_type = 0;
+    let mut byte_size = None;
+    let mut alignment = None;
+    let mut encoding = None;
+    let mut decl_file = None;
+    let mut decl_line = None;
+    let mut signature = None;
+    let mut specification = None;
+    let mut bit_stride = None;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_name => {
+                name = dw_at_name_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), name);
+            }
+            gimli::DW_AT_type => {
+                inner_type = dw_at_type_handler(context, unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), inner_type);
+            }
+            gimli::DW_AT_byte_size => {
+                byte_size = attr.udata_value();
+                trace!("   {}: {:?}", attr.name(), byte_size);
+            }
+            gimli::DW_AT_alignment => {
+                alignment = attr.udata_value();
+                trace!("   {}: {:?}", attr.name(), alignment);
+            }
+            gimli::DW_AT_encoding => {
+                if let gimli::AttributeValue::Encoding(value) = attr.value() {
+                    encoding = Some(value.to_string());
+                }
+                trace!("   {}: {:?}", attr.name(), encoding);
+            }
+            gimli::DW_AT_decl_file => {
+                decl_file = dw_at_file_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), decl_file);
+            }
+            gimli::DW_AT_decl_line => {
+                decl_line = attr.udata_value();
+                trace!("   {}: {:?}", attr.name(), decl_line);
+            }
+            gimli::DW_AT_byte_stride | gimli::DW_AT_bit_stride => {
+                bit_stride = dw_at_stride_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), bit_stride);
+            }
+            gimli::DW_AT_specification => {
+                specification = dw_at_origin_handler(unit, &attr);
+                trace!("   {}: {:?}", attr.name(), specification);
+            }
+            // A declaration whose definition was moved into a type unit, e.g. with -fdebug-types-section.
+            gimli::DW_AT_signature => {
+                signature = Some(dw_at_type_handler(context, unit, &attr)?);
+                trace!("   {}: {:?}", attr.name(), signature);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    // Pointers and references usually omit DW_AT_byte_size, and have the address size of the unit.
+    if byte_size.is_none()
+        && matches!(
+            kind,
+            TypeKind::Pointer | TypeKind::Reference | TypeKind::RvalueReference
+        )
+    {
+        byte_size = Some(u64::from(unit.encoding().address_size));
+    }
+
+    // Member functions and nested types are named after the class they are declared in.
+    let scope = match kind {
+        TypeKind::Struct | TypeKind::Class | TypeKind::Union if !name.is_empty() => {
+            Some(name.clone())
+        }
+        _ => None,
+    };
+    // A definition outside of the namespace of its declaration, e.g. a class in a type unit, takes the
+    // qualified name of the declaration.
+    let declared_name = specification
+        .and_then(|origin| context.types.get(&dw_offset_handler(unit, origin)))
+        .map(|declaration| declaration.name.clone())
+        .filter(|declared_name| !name.is_empty() && !declared_name.is_empty());
+    if let Some(declared_name) = declared_name {
+        name = declared_name;
+    } else if !name.is_empty() {
+        name = scoped_name(context, &name);
+    }
+
+    // Anonymous types are told apart by where they are declared.
+    let offset = dw_offset_handler(unit, entry.offset());
+    let tag = match kind {
+        TypeKind::Struct => Some("struct"),
+        TypeKind::Class => Some("class"),
+        TypeKind::Union => Some("union"),
+        TypeKind::Enum => Some("enum"),
+        _ => None,
+    };
+    if let (Some(tag), true) = (tag, name.is_empty()) {
+        name = match (decl_file, decl_line) {
+            (Some(file), Some(line)) => format!("(anonymous {} at {}:{})", tag, file, line),
+            _ => format!("(anonymous {} @{})", tag, offset),
+        };
+    }
+
+    context.types.insert(
+        offset,
+        Type {
+            kind,
+            name,
+            inner_type,
+            byte_size,
+            alignment,
+            count: None,
+            dimensions: Vec::new(),
+            bit_stride,
+            strides: Vec::new(),
+            encoding,
+            members: Vec::new(),
+            enumerators: Vec::new(),
+            parameters: Vec::new(),
+            variadic: false,
+        },
+    );
+    if let Some(definition) = signature.filter(|&definition| definition != 0) {
+        context.signature_declarations.push((offset, definition));
+    }
+
+    // The following DW_TAG_member, DW_TAG_enumerator, DW_TAG_subrange_type, and DW_TAG_formal_parameter
+    // children belong to this type.
+    if matches!(
+        kind,
+        TypeKind::Struct
+            | TypeKind::Class
+            | TypeKind::Union
+            | TypeKind::Enum
+            | TypeKind::Array
+            | TypeKind::Subroutine
+    ) {
+        context.type_scopes.push((depth, offset));
+    }
+    if let Some(scope) = scope {
+        context.name_scopes.push((depth, scope));
+    }
+
+    Ok(())
+}
+
+/// Handler for DW_TAG_compile_unit, the root DIE of the unit, and for the root DIEs of the other kinds
+/// of units, e.g. DW_TAG_type_unit, whose language decides the defaults of the types they define.
+/// we are interested in the producer and language of the unit, and whether it has the entry point of the
+/// program, the name and comp_dir are already read by gimli.
+fn dw_tag_compile_unit_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<(), gimli::Error> {
+    let mut producer = None;
+    let mut language = None;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_main_subprogram => {
+                context.main_unit = dw_at_flag_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), context.main_unit);
+            }
+            gimli::DW_AT_producer => {
+                producer = Some(dw_at_name_handler(unit, &attr)?);
+                trace!("   {}: {:?}", attr.name(), producer);
+            }
+            gimli::DW_AT_language => {
+                if let gimli::AttributeValue::Language(value) = attr.value() {
+                    language = Some(value);
+                }
+                trace!("   {}: {:?}", attr.name(), language);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    if let Some(compilation_unit) = &mut context.unit {
+        compilation_unit.producer = producer;
+        // Vendor languages without a name in gimli are written as their number, e.g. `DW_LANG_0x8001`.
+        compilation_unit.language = language.map(|language| match language.static_string() {
+            Some(name) => name.to_string(),
+            None => format!("DW_LANG_{:#x}", language.0),
+        });
+        compilation_unit.language_code = language.map(|language| language.0);
+    }
+    Ok(())
+}
+
+/// Handler for DW_TAG_namespace, which prefixes the names of the subprograms and types inside it.
+/// we are interested in the name of the namespace, which is missing for anonymous namespaces.
+fn dw_tag_namespace_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+    depth: isize,
+) -> Result<(), gimli::Error> {
+    let mut name = String::from("(anonymous namespace)");
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_name => {
+                name = dw_at_name_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), name);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    context.name_scopes.push((depth, name));
+    Ok(())
+}
+
+/// Prefix a name with the enclosing namespaces, e.g. `baz` in `foo::bar` is `foo::bar::baz`.
+fn scoped_name(context: &UnitContext, name: &str) -> String {
+    let mut scoped = String::new();
+    for (_, scope) in &context.name_scopes {
+        scoped.push_str(scope);
+        scoped.push_str("::");
+    }
+    scoped.push_str(name);
+    scoped
+}
+
+/// Get the qualified name of the subprogram or global variable that the DIE refers to with
+/// DW_AT_abstract_origin or DW_AT_specification, if it has already been visited.
+fn dw_origin_qualified_name<'a>(
+    context: &UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<Option<String>, gimli::Error> {
+    dw_origin_lookup(&context.qualified_names, unit, entry)
+}
+
+/// Look up the DIE that the DIE refers to with DW_AT_abstract_origin or DW_AT_specification in a map
+/// keyed by `.debug_info` offset, which only has the DIEs that have already been visited.
+fn dw_origin_lookup<'a>(
+    names: &HashMap<usize, String>,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<Option<String>, gimli::Error> {
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        if !matches!(
+            attr.name(),
+            gimli::DW_AT_abstract_origin | gimli::DW_AT_specification
+        ) {
+            continue;
+        }
+        if let Some(origin) = dw_at_origin_handler(unit, &attr) {
+            let offset = dw_offset_handler(unit, origin);
+            if let Some(name) = names.get(&offset) {
+                return Ok(Some(name.clone()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Handler for DW_TAG_member, which is a field of the enclosing structure or union type.
+/// we are interested in the name, type, and byte offset of the field.
+fn dw_tag_member_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<(), gimli::Error> {
+    let mut name = String::new();
+    let mut member_type = 0;
+    let mut offset = None;
+    let mut bit_size = None;
+    let mut data_bit_offset = None;
+    let mut bit_offset = None;
+    let mut byte_size = None;
+    let mut alignment = None;
+    let mut accessibility = None;
+    let mut declaration = false;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_name => {
+                name = dw_at_name_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), name);
+            }
+            gimli::DW_AT_type => {
+                member_type = dw_at_type_handler(context, unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), member_type);
+            }
+            gimli::DW_AT_data_member_location => {
+                offset = dw_at_data_member_location_handler(unit, &attr);
+                trace!("   {}: {:?}", attr.name(), offset);
+            }
+            gimli::DW_AT_bit_size => {
+                bit_size = attr.udata_value();
+                trace!("   {}: {:?}", attr.name(), bit_size);
+            }
+            gimli::DW_AT_data_bit_offset => {
+                data_bit_offset = attr.udata_value();
+                trace!("   {}: {:?}", attr.name(), data_bit_offset);
+            }
+            gimli::DW_AT_bit_offset => {
+                bit_offset = attr.sdata_value();
+                trace!("   {}: {:?}", attr.name(), bit_offset);
+            }
+            gimli::DW_AT_byte_size => {
+                byte_size = attr.udata_value();
+                trace!("   {}: {:?}", attr.name(), byte_size);
+            }
+            gimli::DW_AT_alignment => {
+                alignment = attr.udata_value();
+                trace!("   {}: {:?}", attr.name(), alignment);
+            }
+            gimli::DW_AT_declaration => {
+                declaration = dw_at_flag_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), declaration);
+            }
+            gimli::DW_AT_accessibility => {
+                accessibility = match attr.value() {
+                    gimli::AttributeValue::Accessibility(gimli::DW_ACCESS_public) => Some("public"),
+                    gimli::AttributeValue::Accessibility(gimli::DW_ACCESS_protected) => {
+                        Some("protected")
+                    }
+                    gimli::AttributeValue::Accessibility(gimli::DW_ACCESS_private) => {
+                        Some("private")
+                    }
+                    _ => None,
+                };
+                trace!("   {}: {:?}", attr.name(), accessibility);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    // Before DWARF5, a static data member is declared as a member, and defined as a global variable.
+    if declaration && !name.is_empty() {
+        let offset = dw_offset_handler(unit, entry.offset());
+        context
+            .qualified_names
+            .insert(offset, scoped_name(context, &name));
+    }
+
+    // Before DWARF4, DW_AT_bit_offset counts from the most significant bit of a storage unit of
+    // DW_AT_byte_size bytes at DW_AT_data_member_location, so it depends on the byte order.
+    if data_bit_offset.is_none() {
+        if let (Some(bit_size), Some(bit_offset), Some(byte_size)) =
+            (bit_size, bit_offset, byte_size)
+        {
+            let base = offset.unwrap_or(0) as i64 * 8;
+            let bits = if unit.dwarf.debug_info.reader().endian().is_big_endian() {
+                base + bit_offset
+            } else {
+                base + byte_size as i64 * 8 - bit_offset - bit_size as i64
+            };
+            data_bit_offset = u64::try_from(bits).ok();
+        }
+    }
+
+    // Members outside of a structure or union, e.g. in a Rust variant part, are ignored.
+    let parent = match context.type_scopes.last() {
+        Some(&(_, parent)) => parent,
+        None => return Ok(()),
+    };
+    // Only C++ has a default accessibility, which depends on the class key of the containing type.
+    let language = context.unit.as_ref().and_then(|unit| unit.language_code);
+    let cplusplus = matches!(
+        language.map(gimli::DwLang),
+        Some(
+            gimli::DW_LANG_C_plus_plus
+                | gimli::DW_LANG_C_plus_plus_03
+                | gimli::DW_LANG_C_plus_plus_11
+                | gimli::DW_LANG_C_plus_plus_14
+                | gimli::DW_LANG_C_plus_plus_17
+                | gimli::DW_LANG_C_plus_plus_20
+                | gimli::DW_LANG_ObjC_plus_plus
+        )
+    );
+    if let Some(parent) = context.types.get_mut(&parent) {
+        let accessibility = match accessibility {
+            Some(accessibility) => Some(accessibility),
+            None if cplusplus && parent.kind == TypeKind::Class => Some("private"),
+            None if cplusplus => Some("public"),
+            None => None,
+        };
+        parent.members.push(Member {
+            name,
+            member_type,
+            offset,
+            bit_size,
+            data_bit_offset,
+            alignment,
+            accessibility: accessibility.map(str::to_string),
+        });
+    }
+
+    Ok(())
+}
+
+/// Handler for DW_TAG_enumerator, which is a named value of the enclosing enumeration type.
+/// we are interested in the name and the value of the enumerator.
+/// The value is kept as read, and normalized to the underlying type once all types are known.
+fn dw_tag_enumerator_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<(), gimli::Error> {
+    let mut name = String::new();
+    let mut value = None;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_name => {
+                name = dw_at_name_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), name);
+            }
+            gimli::DW_AT_const_value => {
+                value = match attr.value() {
+                    gimli::AttributeValue::Sdata(value) => Some(i128::from(value)),
+                    _ => attr.udata_value().map(i128::from),
+                };
+                trace!("   {}: {:?}", attr.name(), value);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    let (Some(&(_, parent)), Some(value)) = (context.type_scopes.last(), value) else {
+        return Ok(());
+    };
+    if let Some(parent) = context.types.get_mut(&parent) {
+        if parent.kind == TypeKind::Enum {
+            parent.enumerators.push(Enumerator { name, value });
+        }
+    }
+
+    Ok(())
+}
+
+/// Handler for DW_TAG_template_type_parameter and DW_TAG_template_value_parameter, which are the
+/// template parameters of a generic subprogram, or of a generic type, which are not collected.
+/// we are interested in the name, the type, and the value of the parameter.
+fn dw_tag_template_param_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+    depth: isize,
+) -> Result<(), gimli::Error> {
+    let key = match context.subprogram_scopes.last() {
+        Some((scope_depth, Some(key))) if scope_depth + 1 == depth => key.clone(),
+        _ => return dw_tag_default_handler(unit, entry),
+    };
+    let kind = if entry.tag() == gimli::DW_TAG_template_value_parameter {
+        TemplateParamKind::Value
+    } else {
+        TemplateParamKind::Type
+    };
+    let mut name = String::new();
+    let mut param_type = 0;
+    let mut value = None;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_name => {
+                name = dw_at_name_handler(unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), name);
+            }
+            gimli::DW_AT_type => {
+                param_type = dw_at_type_handler(context, unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), param_type);
+            }
+            gimli::DW_AT_const_value => {
+                value = match attr.value() {
+                    gimli::AttributeValue::Sdata(value) => Some(i128::from(value)),
+                    _ => attr.udata_value().map(i128::from),
+                };
+                trace!("   {}: {:?}", attr.name(), value);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    // A parameter replaces the one of the same name inherited from the abstract instance or declaration.
+    let param = TemplateParam {
+        kind,
+        name,
+        param_type,
+        value,
+    };
+    if let Some(subprogram) = context.subprograms.get_mut(&key) {
+        let params = &mut subprogram.template_params;
+        match params
+            .iter_mut()
+            .find(|existing| !param.name.is_empty() && existing.name == param.name)
+        {
+            Some(existing) => *existing = param,
+            None => params.push(param),
+        }
+    }
+
+    Ok(())
+}
+
+/// Handler for DW_TAG_formal_parameter and DW_TAG_unspecified_parameters, which are the parameters
+/// of the enclosing subroutine type, e.g. of a function pointer, or of the enclosing subprogram.
+/// we are interested in the type of the parameter, and whether the parameters are variadic.
+fn dw_tag_parameter_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+    depth: isize,
+) -> Result<(), ParseError> {
+    // The parameters of subprograms are children of the subprogram, not of a type, and are variables.
+    let parent = match context.type_scopes.last() {
+        Some(&(type_depth, parent)) if type_depth + 1 == depth => parent,
+        _ if entry.tag() == gimli::DW_TAG_formal_parameter => {
+            return dw_tag_variable_handler(context, unit, entry);
+        }
+        _ => return Ok(dw_tag_default_handler(unit, entry)?),
+    };
+    let mut param_type = 0;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_type => {
+                param_type = dw_at_type_handler(context, unit, &attr)?;
+                trace!("   {}: {:?}", attr.name(), param_type);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+
+    if let Some(parent) = context.types.get_mut(&parent) {
+        if parent.kind == TypeKind::Subroutine {
+            if entry.tag() == gimli::DW_TAG_unspecified_parameters {
+                parent.variadic = true;
+            } else {
+                parent.parameters.push(param_type);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handler for DW_TAG_subrange_type, which is a dimension of the enclosing array type.
+/// we compute the element count from DW_AT_count, or from DW_AT_upper_bound and DW_AT_lower_bound,
+/// and the count is dynamic if any of them is only known at run time.
+fn dw_tag_subrange_handler<'a>(
+    context: &mut UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<(), gimli::Error> {
+    let mut count = None;
+    let mut lower_bound = None;
+    let mut upper_bound = None;
+    let mut stride = None;
+
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match attr.name() {
+            gimli::DW_AT_count => {
+                count = dw_at_bound_handler(unit, &attr);
+                trace!("   {}: {:?}", attr.name(), count);
+            }
+            gimli::DW_AT_lower_bound => {
+                lower_bound = dw_at_bound_handler(unit, &attr);
+                trace!("   {}: {:?}", attr.name(), lower_bound);
+            }
+            gimli::DW_AT_upper_bound => {
+                upper_bound = dw_at_bound_handler(unit, &attr);
+                trace!("   {}: {:?}", attr.name(), upper_bound);
+            }
+            gimli::DW_AT_byte_stride | gimli::DW_AT_bit_stride => {
+                stride = dw_at_stride_handler(&attr);
+                trace!("   {}: {:?}", attr.name(), stride);
+            }
+            _ => {
+                // trace!("   {}: Unparsed Attribute", attr.name());
+                continue;
+            }
+        }
+    }
+    let count = match (
+        count,
+        lower_bound.unwrap_or(Dimension::Count(0)),
+        upper_bound,
+    ) {
+        (Some(count), _, _) => count,
+        (None, Dimension::Count(lower), Some(Dimension::Count(upper))) => upper
+            .checked_add(1)
+            .and_then(|count| count.checked_sub(lower))
+            .map_or(Dimension::Unbounded, Dimension::Count),
+        (None, _, Some(Dimension::Dynamic(offset))) => Dimension::Dynamic(offset),
+        (None, Dimension::Dynamic(offset), Some(_)) => Dimension::Dynamic(offset),
+        _ => Dimension::Unbounded,
+    };
+
+    // Each subrange is a dimension, and the element count of the array is their product.
+    let parent = match context.type_scopes.last() {
+        Some(&(_, parent)) => parent,
+        None => return Ok(()),
+    };
+    if let Some(parent) = context.types.get_mut(&parent) {
+        if parent.kind == TypeKind::Array {
+            parent.dimensions.push(count);
+            parent.strides.push(stride);
+            parent.count = parent
+                .dimensions
+                .iter()
+                .try_fold(1u64, |total, count| match count {
+                    Dimension::Count(count) => total.checked_mul(*count),
+                    _ => None,
+                });
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolve the name of the type at `offset` in the type map, e.g. `const char *`.
+/// Structure, union, and enumeration types are named after their tag and are not expanded.
+/// A chain of types deeper than `max_depth` is truncated to `...`.
+/// Returns None if the type is not in the map.
+fn resolve_type_name(
+    types: &BTreeMap<usize, Type>,
+    offset: usize,
+    max_depth: usize,
+) -> Option<String> {
+    /// Resolve the name recursively as a C declarator without an identifier, split into the part
+    /// on the left of the identifier and the part on its right, e.g. `int (*` and `)[4]` for a
+    /// pointer to an array. `visited` holds the offsets on the current path.
+    /// Malformed or self-referential DWARF can make a type refer back to itself through
+    /// typedef/qualifier/pointer chains, so a revisited offset is emitted in a truncated form
+    /// (its name only) instead of being expanded again.
+    fn resolve(
+        types: &BTreeMap<usize, Type>,
+        offset: usize,
+        visited: &mut HashSet<usize>,
+        max_depth: usize,
+    ) -> Option<(String, String)> {
+        if offset == 0 {
+            return Some(("void".to_string(), String::new()));
+        }
+        if visited.len() >= max_depth {
+            warn!(
+                "Type chain at {:#x} is deeper than {}, truncating its name",
+                offset, max_depth
+            );
+            return Some(("...".to_string(), String::new()));
+        }
+        let node = types.get(&offset)?;
+        let tagged = |tag: &str| match node.name.as_str() {
+            "" => tag.to_string(),
+            name => format!("{} {}", tag, name),
+        };
+        if !visited.insert(offset) {
+            let name = match node.kind {
+                TypeKind::Struct => tagged("struct"),
+                TypeKind::Class => tagged("class"),
+                TypeKind::Union => tagged("union"),
+                TypeKind::Enum => tagged("enum"),
+                _ if !node.name.is_empty() => node.name.clone(),
+                _ => "...".to_string(),
+            };
+            return Some((name, String::new()));
+        }
+
+        let mut inner = || resolve(types, node.inner_type, visited, max_depth);
+        let name = match node.kind {
+            TypeKind::Struct => Some((tagged("struct"), String::new())),
+            TypeKind::Class => Some((tagged("class"), String::new())),
+            TypeKind::Union => Some((tagged("union"), String::new())),
+            TypeKind::Enum => Some((tagged("enum"), String::new())),
+            // Languages like Rust name their pointer and base types, e.g. `&str`.
+            _ if !node.name.is_empty() => Some((node.name.clone(), String::new())),
+            TypeKind::Pointer => inner().map(|inner| declarator(inner, "*")),
+            TypeKind::Reference => inner().map(|inner| declarator(inner, "&")),
+            TypeKind::RvalueReference => inner().map(|inner| declarator(inner, "&&")),
+            TypeKind::Const => inner().map(|inner| qualify(inner, "const")),
+            TypeKind::Volatile => inner().map(|inner| qualify(inner, "volatile")),
+            TypeKind::Restrict => inner().map(|inner| qualify(inner, "restrict")),
+            TypeKind::Array => inner().map(|(left, right)| {
+                let mut dimensions = String::new();
+                // A variable-length array is written as in a C prototype, e.g. `int [*]`.
+                for count in &node.dimensions {
+                    match count {
+                        Dimension::Count(count) => dimensions.push_str(&format!("[{}]", count)),
+                        Dimension::Dynamic(_) => dimensions.push_str("[*]"),
+                        Dimension::Unbounded => dimensions.push_str("[]"),
+                    }
+                }
+                if node.dimensions.is_empty() {
+                    dimensions.push_str("[]");
+                }
+                (left, format!("{}{}", dimensions, right))
+            }),
+            TypeKind::Subroutine => inner().and_then(|(left, right)| {
+                let mut parameters = node
+                    .parameters
+                    .iter()
+                    .map(|&parameter| {
+                        let (left, right) = resolve(types, parameter, visited, max_depth)?;
+                        Some(format!("{}{}", left, right))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                if node.variadic {
+                    parameters.push("...".to_string());
+                }
+                Some((left, format!("({}){}", parameters.join(", "), right)))
+            }),
+            TypeKind::Base | TypeKind::Typedef => Some((node.name.clone(), String::new())),
+            // An unnamed DW_TAG_unspecified_type stands for void, as a missing DW_AT_type does.
+            TypeKind::Unspecified => Some(("void".to_string(), String::new())),
+        };
+        visited.remove(&offset);
+        name
+    }
+
+    /// A pointer or reference declarator binds to the right of its pointee, e.g. `char **`, and
+    /// needs parentheses around it if the pointee has a suffix, e.g. `int (*)[4]` for an array or
+    /// `int (*)(char, double)` for a function.
+    fn declarator((left, right): (String, String), operator: &str) -> (String, String) {
+        if !right.is_empty() && !right.starts_with(')') {
+            let open = if left.ends_with(['*', '&']) {
+                "("
+            } else {
+                " ("
+            };
+            (
+                format!("{}{}{}", left, open, operator),
+                format!("){}", right),
+            )
+        } else if left.ends_with(['*', '&']) {
+            (format!("{}{}", left, operator), right)
+        } else {
+            (format!("{} {}", left, operator), right)
+        }
+    }
+
+    /// Qualifiers go before a plain type (`const int`), but after the `*` of a pointer (`int * const`),
+    /// as a qualifier on the left of a `*` applies to the pointee instead.
+    fn qualify((left, right): (String, String), qualifier: &str) -> (String, String) {
+        if left.ends_with(['*', '&']) {
+            (format!("{} {}", left, qualifier), right)
+        } else {
+            (format!("{} {}", qualifier, left), right)
+        }
+    }
+
+    let (left, right) = resolve(types, offset, &mut HashSet::new(), max_depth)?;
+    Some(format!("{}{}", left, right))
+}
+
+/// Handler for other DW_TAG_*, which is currently not parsed.
+/// we just trace all the attributes, where a string that cannot be resolved is traced with its error,
+/// and nothing is read unless tracing is enabled.
+fn dw_tag_default_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+) -> Result<(), gimli::Error> {
+    if !log_enabled!(Level::Trace) {
+        return Ok(());
+    }
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        match dw_at_name_handler(unit, &attr) {
+            Ok(value) => trace!("   {}: {:?}", attr.name(), value),
+            Err(err) => trace!("   {}: {:?} ({})", attr.name(), attr.value(), err),
+        }
+    }
+    Ok(())
+}
+
+/// Handler for DW_AT_name, which is a string attribute.
+/// we convert the attribute value from a DebugStrRef(offset) to a string.
+fn dw_at_name_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+) -> Result<String, gimli::Error> {
+    match unit.attr_string(attr.value()) {
+        Ok(string) => Ok(string.to_string_lossy()?.to_string()),
+        // Only names that are not encoded as strings at all are kept in their debug format,
+        // a string reference that cannot be resolved is an error.
+        Err(gimli::Error::ExpectedStringAttributeValue) => Ok(format!("{:?}", attr.value())),
+        Err(err) => Err(err),
+    }
+}
+
+/// Handler for flag attributes such as DW_AT_deleted and DW_AT_main_subprogram.
+/// DW_FORM_flag_present is also read as `Flag(true)` by gimli.
+fn dw_at_flag_handler(attr: &gimli::Attribute<Reader>) -> bool {
+    matches!(attr.value(), gimli::AttributeValue::Flag(true))
+}
+
+/// Handler for DW_AT_decl_file and DW_AT_call_file, which are indices into the file table of the line program.
+/// we join the file name with its directory and the DW_AT_comp_dir of the unit, where directory 0 is the
+/// compilation directory. Directories and file names that are already absolute replace what comes before.
+fn dw_at_file_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+) -> Result<Option<String>, gimli::Error> {
+    match (dw_at_file_index_handler(unit, attr), &unit.line_program) {
+        (Some(index), Some(program)) => dw_line_file_handler(unit, program.header(), index),
+        _ => Ok(None),
+    }
+}
+
+/// Handler for the raw index of DW_AT_decl_file and DW_AT_call_file.
+/// we keep it as is, except for index 0 before DWARF5, where file indices are 1-based and 0 means that
+/// no file is specified, while gimli maps file 0 to the primary source file. In DWARF5, file 0 is the
+/// primary source file.
+fn dw_at_file_index_handler(
+    unit: &gimli::UnitRef<Reader>,
+    attr: &gimli::Attribute<Reader>,
+) -> Option<u64> {
+    let index = attr.udata_value()?;
+    let header = unit.line_program.as_ref()?.header();
+    if header.version() < 5 && index == 0 {
+        return None;
+    }
+    Some(index)
+}
+
+/// Resolve the paths of the whole file table of the line program, indexed as DW_AT_decl_file, so that
+/// before DWARF5 the entry 0 is the primary source file that gimli maps file 0 to.
+fn dw_line_files_handler(unit: &gimli::UnitRef<Reader>) -> Result<Vec<String>, gimli::Error> {
+    let header = match &unit.line_program {
+        Some(program) => program.header(),
+        None => return Ok(Vec::new()),
+    };
+    let count = header.file_names().len() as u64 + u64::from(header.version() < 5);
+    (0..count)
+        .map(|index| Ok(dw_line_file_handler(unit, header, index)?.unwrap_or_default()))
+        .collect()
+}
+
+/// Resolve the path of the file at `index` in the file table of the line program header.
+fn dw_line_file_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    header: &gimli::LineProgramHeader<Reader<'a>>,
+    index: u64,
+) -> Result<Option<String>, gimli::Error> {
+    let file = match header.file(index) {
+        Some(file) => file,
+        None => return Ok(None),
+    };
+
+    // Each component replaces the ones before if it is absolute, so absolute paths are kept as is.
+    let mut path = path::PathBuf::new();
+    if let Some(comp_dir) = &unit.comp_dir {
+        path.push(comp_dir.to_string_lossy()?.as_ref());
+    }
+    if file.directory_index() != 0 {
+        if let Some(directory) = header.directory(0) {
+            path.push(unit.attr_string(directory)?.to_string_lossy()?.as_ref());
+        }
+    }
+    if let Some(directory) = file.directory(header) {
+        path.push(unit.attr_string(directory)?.to_string_lossy()?.as_ref());
+    }
+    path.push(
+        unit.attr_string(file.path_name())?
+            .to_string_lossy()?
+            .as_ref(),
+    );
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
+
+/// Handler for DW_AT_type, which is a reference to another DW_TAG_type.
+/// we convert the attribute value from a UnitRef(offset) to a usize, which stands for a DW_TAG_type node.
+/// The offset is made relative to the start of `.debug_info` (unit header offset plus DIE offset),
+/// so that type references stay unique after merging the units.
+/// A DW_FORM_ref_addr reference, i.e. a DebugInfoRef, may point into another unit, e.g. after
+/// the linker or `dwz` merged identical types, and is already relative to the start of `.debug_info`.
+/// A DW_FORM_ref_sig8 reference, i.e. a DebugTypesRef, is the signature of a type unit, and resolves
+/// to the type that the unit defines. A signature without a type unit in the file, e.g. one in a
+/// `.dwo` file, resolves to 0 like void.
+fn dw_at_type_handler<'a>(
+    context: &UnitContext,
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+) -> Result<usize, gimli::Error> {
+    match attr.value() {
+        gimli::AttributeValue::UnitRef(offset) => Ok(dw_offset_handler(unit, offset)),
+        gimli::AttributeValue::DebugInfoRef(offset) => Ok(offset.0),
+        gimli::AttributeValue::DebugTypesRef(signature) => {
+            match context.signatures.get(&signature) {
+                Some(&offset) => Ok(offset),
+                None => {
+                    debug!(
+                        "   {}: Unknown type signature {:#x}",
+                        attr.name(),
+                        signature.0
+                    );
+                    Ok(0)
+                }
+            }
+        }
+        _ => Err(gimli::Error::UnsupportedAttributeForm),
+    }
+}
+
+/// Handler for DW_AT_calling_convention, which is a DW_CC_* constant.
+/// we convert it to its name, including the vendor conventions of GCC and LLVM that gimli does not name,
+/// and unknown vendor values are written as their number, e.g. `DW_CC_0x90`.
+fn dw_at_calling_convention_handler(attr: &gimli::Attribute<Reader>) -> Option<String> {
+    let gimli::AttributeValue::CallingConvention(value) = attr.value() else {
+        return None;
+    };
+    let name = match value.0 {
+        0x40 => "DW_CC_GNU_renesas_sh",
+        0x41 => "DW_CC_GNU_borland_fastcall_i386",
+        0xb0 => "DW_CC_BORLAND_safecall",
+        0xb1 => "DW_CC_BORLAND_stdcall",
+        0xb2 => "DW_CC_BORLAND_pascal",
+        0xb3 => "DW_CC_BORLAND_msfastcall",
+        0xb4 => "DW_CC_BORLAND_msreturn",
+        0xb5 => "DW_CC_BORLAND_thiscall",
+        0xb6 => "DW_CC_BORLAND_fastcall",
+        0xc0 => "DW_CC_LLVM_vectorcall",
+        0xc1 => "DW_CC_LLVM_Win64",
+        0xc2 => "DW_CC_LLVM_X86_64SysV",
+        0xc3 => "DW_CC_LLVM_AAPCS",
+        0xc4 => "DW_CC_LLVM_AAPCS_VFP",
+        0xc5 => "DW_CC_LLVM_IntelOclBicc",
+        0xc6 => "DW_CC_LLVM_SpirFunction",
+        0xc7 => "DW_CC_LLVM_OpenCLKernel",
+        0xc8 => "DW_CC_LLVM_Swift",
+        0xc9 => "DW_CC_LLVM_PreserveMost",
+        0xca => "DW_CC_LLVM_PreserveAll",
+        0xcb => "DW_CC_LLVM_X86RegCall",
+        0xcc => "DW_CC_LLVM_M68kRTD",
+        0xcd => "DW_CC_LLVM_PreserveNone",
+        0xce => "DW_CC_LLVM_RISCVVectorCall",
+        0xcf => "DW_CC_LLVM_SwiftTail",
+        0xff => "DW_CC_GDB_IBM_OpenCL",
+        _ => match value.static_string() {
+            Some(name) => name,
+            None => return Some(format!("DW_CC_{:#x}", value.0)),
+        },
+    };
+    Some(name.to_string())
+}
+
+/// Handler for DW_AT_count, DW_AT_lower_bound, and DW_AT_upper_bound, which are the bounds of a subrange.
+/// we convert a constant to a Count, and a reference to a DIE or a location expression (DW_FORM_exprloc,
+/// or a block before DWARF4), which the bound is computed from at run time, to a Dynamic count.
+/// A negative constant, e.g. the upper bound -1 of an empty array, has no count.
+fn dw_at_bound_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+) -> Option<Dimension> {
+    match attr.value() {
+        gimli::AttributeValue::UnitRef(offset) => {
+            Some(Dimension::Dynamic(Some(dw_offset_handler(unit, offset))))
+        }
+        gimli::AttributeValue::DebugInfoRef(offset) => Some(Dimension::Dynamic(Some(offset.0))),
+        gimli::AttributeValue::Exprloc(_) | gimli::AttributeValue::Block(_) => {
+            Some(Dimension::Dynamic(None))
+        }
+        _ => attr.udata_value().map(Dimension::Count),
+    }
+}
+
+/// Handler for DW_AT_byte_stride and DW_AT_bit_stride, which are the distance between the elements of
+/// an array or of a dimension of it.
+/// we convert a constant stride to bits, and a stride computed at run time, e.g. by an expression for
+/// a Fortran array section, to None, as if it were absent.
+fn dw_at_stride_handler(attr: &gimli::Attribute<Reader>) -> Option<u64> {
+    let stride = attr.udata_value()?;
+    if attr.name() == gimli::DW_AT_byte_stride {
+        stride.checked_mul(8)
+    } else {
+        Some(stride)
+    }
+}
+
+/// Convert a DIE offset in the unit to an offset relative to the start of the section,
+/// which is the key of the DIE in the type map.
+/// The offset is the `gimli::ReaderOffset` of `Reader`, i.e. `usize`, so the 8-byte offsets of DWARF64 are
+/// kept intact; gimli already fails with `UnsupportedOffset` when an offset does not fit on the host.
+fn dw_offset_handler(unit: &gimli::UnitRef<Reader>, offset: gimli::UnitOffset) -> usize {
+    dw_section_offset_handler(unit.dwarf, offset.to_unit_section_offset(unit))
+}
+
+/// Convert an offset in `.debug_info` or `.debug_types` to a key of the type map.
+/// The offsets of `.debug_types` continue after the end of `.debug_info`, so that the DIEs of both
+/// sections have distinct keys, and the type units sort after the compilation units.
+fn dw_section_offset_handler(
+    dwarf: &gimli::Dwarf<Reader>,
+    offset: gimli::UnitSectionOffset,
+) -> usize {
+    match offset {
+        gimli::UnitSectionOffset::DebugInfoOffset(offset) => offset.0,
+        gimli::UnitSectionOffset::DebugTypesOffset(offset) => {
+            dwarf.debug_info.reader().len() + offset.0
+        }
+    }
+}
+
+/// Handler for DW_AT_abstract_origin and DW_AT_specification, which are references to another DIE.
+/// we only follow references into the current unit, and return None for anything else.
+fn dw_at_origin_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+) -> Option<gimli::UnitOffset> {
+    match attr.value() {
+        gimli::AttributeValue::UnitRef(offset) => Some(offset),
+        gimli::AttributeValue::DebugInfoRef(offset) => offset.to_unit_offset(&unit.header),
+        _ => None,
+    }
+}
+
+/// Walk the DW_AT_abstract_origin and DW_AT_specification references starting at `entry`,
+/// and call `f` with every attribute of the referenced DIEs, nearest reference first.
+/// A concrete instance points at its abstract instance, which may in turn point at the declaration
+/// inside a class, so both kinds are followed transitively.
+/// The walk stops at the end of the chain, on a loop, or on a reference outside of the unit.
+fn dw_at_origin_chain_handler<'a, F>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
+    mut f: F,
+) -> Result<(), gimli::Error>
+where
+    F: FnMut(&gimli::Attribute<Reader<'a>>) -> Result<(), gimli::Error>,
+{
+    fn push_references<'a>(
+        unit: &gimli::UnitRef<Reader<'a>>,
+        attr: &gimli::Attribute<Reader<'a>>,
+        pending: &mut Vec<gimli::UnitOffset>,
+    ) {
+        if matches!(
+            attr.name(),
+            gimli::DW_AT_abstract_origin | gimli::DW_AT_specification
+        ) {
+            pending.extend(dw_at_origin_handler(unit, attr));
+        }
+    }
+
+    let mut pending = Vec::new();
+    let mut attrs = entry.attrs();
+    while let Some(attr) = attrs.next()? {
+        push_references(unit, &attr, &mut pending);
+    }
+    pending.reverse();
+
+    let mut visited = HashSet::new();
+    while let Some(offset) = pending.pop() {
+        if !visited.insert(offset) {
+            continue;
+        }
+        let origin = unit.entry(offset)?;
+        let mut references = Vec::new();
+        let mut attrs = origin.attrs();
+        while let Some(attr) = attrs.next()? {
+            push_references(unit, &attr, &mut references);
+            f(&attr)?;
+        }
+        references.reverse();
+        pending.extend(references);
+    }
+    Ok(())
+}
+
+/// Handler for DW_AT_frame_base of a subprogram.
+/// we are interested in whether the frame base is exactly `DW_OP_call_frame_cfa`.
+fn dw_at_frame_base_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+) -> Result<bool, gimli::Error> {
+    let gimli::AttributeValue::Exprloc(expression) = attr.value() else {
+        return Ok(false);
+    };
+    let mut operations = expression.operations(unit.encoding());
+    Ok(matches!(
+        (operations.next()?, operations.next()?),
+        (Some(gimli::Operation::CallFrameCFA), None)
+    ))
+}
+
+/// Handler for DW_AT_data_member_location, which is the byte offset of a member in its parent type.
+/// The offset is usually a constant, but may be an expression that computes the address of the member
+/// from the address of the parent, e.g. `DW_OP_plus_uconst <offset>` before DWARF3.
+/// The expression is evaluated with the parent at address 0, and the offset is None when it needs
+/// more than that, e.g. the vtable of the object to find a virtual base class, or when it is malformed,
+/// which is logged so that only the offset of the member is lost.
+fn dw_at_data_member_location_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+) -> Option<u64> {
+    let expression = match attr.value() {
+        gimli::AttributeValue::Exprloc(expression) => expression,
+        gimli::AttributeValue::Block(data) => gimli::Expression(data),
+        value => return value.udata_value(),
+    };
+    dw_op_member_offset_handler(unit, attr, expression).unwrap_or_else(|err| {
+        warn!(
+            "{} in {}, leaving the offset out",
+            ParseError::MalformedExpression(err),
+            attr.name()
+        );
+        None
+    })
+}
+
+/// Evaluate the expression of a DW_AT_data_member_location with the parent at address 0.
+fn dw_op_member_offset_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+    expression: gimli::Expression<Reader<'a>>,
+) -> Result<Option<u64>, gimli::Error> {
+    let mut eval = expression.clone().evaluation(unit.encoding());
+    eval.set_initial_value(0);
+    match eval.evaluate()? {
+        gimli::EvaluationResult::Complete => {}
+        result => {
+            debug!("   {}: Unparsed Expression: {:?}", attr.name(), result);
+            dw_op_log_handler(unit, attr, expression)?;
+            return Ok(None);
+        }
+    }
+    Ok(match eval.value_result() {
+        Some(value) => Some(value.to_u64(u64::MAX)?),
+        None => None,
+    })
+}
+
+/// Handler for DW_AT_vtable_elem_location, which is the index of a virtual function in the vtable.
+/// The index is a location expression, usually `DW_OP_constu <index>`, so it is evaluated like a
+/// static location, whose address is the index, and is None if the expression is malformed.
+fn dw_at_vtable_elem_location_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+) -> Option<u64> {
+    let expression = match attr.value() {
+        gimli::AttributeValue::Exprloc(expression) => expression,
+        gimli::AttributeValue::Block(data) => gimli::Expression(data),
+        value => return value.udata_value(),
+    };
+    match dw_op_lenient_handler(unit, attr, expression).0 {
+        Some(Location::Address(index)) => Some(index),
+        _ => None,
+    }
+}
+
+/// Handler for DW_AT_location, which is a location expression or a location list.
+/// A location list is read from `.debug_loc`/`.debug_loclists`, and each entry is evaluated on its own.
+/// An empty list means the variable is optimized out, while None means the attribute could not be parsed.
+/// The storage class of a location list is the one of its first entry.
+/// The raw bytes of the first expression without a location are kept, so that it can be decoded by hand.
+fn dw_at_location_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+) -> Result<Option<LocationEntries>, ParseError> {
+    if let gimli::AttributeValue::Exprloc(expression) = attr.value() {
+        let (value, storage_class) = dw_op_lenient_handler(unit, attr, expression.clone());
+        let raw_location = match value {
+            Some(_) => None,
+            None => Some(dw_op_raw_handler(expression)?),
+        };
+        let location = vec![VariableLocation {
+            pc_range: None,
+            location: value,
+        }];
+        return Ok(Some((location, storage_class, raw_location)));
+    }
+
+    let mut location = Vec::new();
+    let mut storage_class = None;
+    let mut raw_location = None;
+    match unit.attr_locations(attr.value())? {
+        Some(mut locations) => {
+            while let Some(entry) = locations.next()? {
+                trace!(
+                    "   {}: [{:#x}, {:#x})",
+                    attr.name(),
+                    entry.range.begin,
+                    entry.range.end
+                );
+                let (value, entry_class) = dw_op_lenient_handler(unit, attr, entry.data.clone());
+                if location.is_empty() {
+                    storage_class = entry_class;
+                }
+                if value.is_none() && raw_location.is_none() {
+                    raw_location = Some(dw_op_raw_handler(entry.data)?);
+                }
+                location.push(VariableLocation {
+                    pc_range: Some((entry.range.begin, entry.range.end)),
+                    location: value,
+                });
+            }
+        }
+        None => {
+            debug!("   {}: Unparsed Location: {:?}", attr.name(), attr.value());
+            return Ok(None);
+        }
+    }
+    if location.is_empty() {
+        trace!("   {}: Optimized Out", attr.name());
+        storage_class = Some(StorageClass::OptimizedOut);
+    }
+    Ok(Some((location, storage_class, raw_location)))
+}
+
+/// Format the bytes of an expression as a hex string, e.g. `9108` for `DW_OP_fbreg 8`.
+fn dw_op_raw_handler(expression: gimli::Expression<Reader>) -> Result<String, gimli::Error> {
+    let bytes = expression.0.to_slice()?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Evaluate a location expression with `dw_op_handler`, where an expression that cannot be decoded or
+/// evaluated is logged and has no location, so that only the location of its variable is lost, and its
+/// bytes are kept as the raw location.
+fn dw_op_lenient_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+    expression: gimli::Expression<Reader<'a>>,
+) -> (Option<Location>, Option<StorageClass>) {
+    dw_op_handler(unit, attr, expression).unwrap_or_else(|err| {
+        warn!(
+            "{} in {}, leaving the location out",
+            ParseError::MalformedExpression(err),
+            attr.name()
+        );
+        (None, None)
+    })
+}
+
+/// Evaluate a single location expression.
+/// we evaluate the expression into the `Location` it describes, e.g. a frame offset or an address.
+/// The location is returned together with the storage class, and is None for unparsed expressions.
+fn dw_op_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+    expression: gimli::Expression<Reader<'a>>,
+) -> Result<(Option<Location>, Option<StorageClass>), gimli::Error> {
+    // Most local variables are a single `DW_OP_fbreg <offset>`, whose operand is the offset itself.
+    let mut operations = expression.clone().operations(unit.encoding());
+    if let (Some(gimli::Operation::FrameOffset { offset }), None) =
+        (operations.next()?, operations.next()?)
+    {
+        trace!("   {}: DW_OP_fbreg {}", attr.name(), offset);
+        return Ok((Some(Location::Stack(offset)), Some(StorageClass::Stack)));
+    }
+
+    // Parameters of optimized code are often the value of their register at the entry of the
+    // subprogram, i.e. `DW_OP_entry_value(DW_OP_regN) DW_OP_stack_value` (or DW_OP_GNU_entry_value).
+    if let Some(number) = dw_op_entry_value_handler(unit, expression.clone())? {
+        trace!("   {}: DW_OP_entry_value(DW_OP_reg{})", attr.name(), number);
+        return Ok((
+            Some(Location::EntryValue { number, name: None }),
+            Some(StorageClass::EntryValue),
+        ));
+    }
+
+    // Compound expressions are evaluated.
+    let mut eval = expression.clone().evaluation(unit.encoding());
+    let mut frame_based = false;
+    let mut result = eval.evaluate()?;
+    loop {
+        match result {
+            gimli::EvaluationResult::Complete => break,
+            // The frame base is unknown statically.
+            // Set the frame base to 0 to calculate the offset.
+            gimli::EvaluationResult::RequiresFrameBase => {
+                frame_based = true;
+                result = eval.resume_with_frame_base(0)?;
+            }
+            // DW_OP_addr is an absolute address, which is the location of a static variable.
+            gimli::EvaluationResult::RequiresRelocatedAddress(address) => {
+                result = eval.resume_with_relocated_address(address)?;
+            }
+            // DW_OP_addrx and DW_OP_constx are an index into `.debug_addr`, e.g. static variables in DWARF5.
+            gimli::EvaluationResult::RequiresIndexedAddress { index, .. } => {
+                let address = unit.address(index)?;
+                result = eval.resume_with_indexed_address(address)?;
+            }
+            // Any other use of an entry value needs the registers of the caller, which are unknown statically.
+            gimli::EvaluationResult::RequiresEntryValue(_) => {
+                debug!("   {}: Unresolved Entry Value", attr.name());
+                dw_op_log_handler(unit, attr, expression)?;
+                return Ok((None, None));
+            }
+            // DW_OP_form_tls_address is an offset into the thread-local storage block.
+            gimli::EvaluationResult::RequiresTls(_) => {
+                trace!("   {}: Thread Local", attr.name());
+                return Ok((None, Some(StorageClass::ThreadLocal)));
+            }
+            // Unparsed Expression, print the result and the operations that led to it.
+            _ => {
+                debug!("   {}: Unparsed Expression: {:?}", attr.name(), result);
+                dw_op_log_handler(unit, attr, expression)?;
+                return Ok((None, None));
+            }
+        }
+    }
+
+    // The expression ends with a piece, which describes where the value lives or the value itself.
+    if let Some(piece) = eval.as_result().first() {
+        trace!("   {}: {:?}", attr.name(), eval.as_result());
+        return Ok(match piece.location.clone() {
+            gimli::Location::Address { address } if frame_based => (
+                Some(Location::Stack(address as i64)),
+                Some(StorageClass::Stack),
+            ),
+            gimli::Location::Address { address } => {
+                (Some(Location::Address(address)), Some(StorageClass::Static))
+            }
+            gimli::Location::Register { register } => (
+                Some(Location::Register {
+                    number: register.0,
+                    name: None,
+                }),
+                Some(StorageClass::Register),
+            ),
+            // DW_OP_stack_value, the value is computed rather than stored.
+            gimli::Location::Value { value } => {
+                let value = match value.convert(gimli::ValueType::I64, 0xFFFFFFFFFFFFFFFF)? {
+                    gimli::Value::I64(value) => Some(Location::Value(value)),
+                    _ => None,
+                };
+                (value, Some(StorageClass::Constant))
+            }
+            // DW_OP_implicit_value, the bytes of the value are in the expression itself.
+            gimli::Location::Bytes { value } => (
+                Some(Location::Bytes(value.to_slice()?.into_owned())),
+                Some(StorageClass::Constant),
+            ),
+            gimli::Location::Empty => (Some(Location::Optimized), Some(StorageClass::OptimizedOut)),
+            gimli::Location::ImplicitPointer { .. } => (None, None),
+        });
+    }
+
+    // Otherwise the top of the stack is the address of the variable.
+    let value = match eval.value_result() {
+        Some(value) => value.convert(gimli::ValueType::I64, 0xFFFFFFFFFFFFFFFF)?,
+        None => return Ok((None, None)),
+    };
+    trace!("   {}: {:?}", attr.name(), value);
+    match value {
+        gimli::Value::I64(val) if frame_based => {
+            Ok((Some(Location::Stack(val)), Some(StorageClass::Stack)))
+        }
+        gimli::Value::I64(val) => Ok((
+            Some(Location::Address(val as u64)),
+            Some(StorageClass::Static),
+        )),
+        _ => Ok((None, Some(StorageClass::Static))),
+    }
+}
+
+/// Get the register of an expression that is the entry value of a single register, i.e.
+/// `DW_OP_entry_value(DW_OP_regN) DW_OP_stack_value`, and None for any other expression.
+fn dw_op_entry_value_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    expression: gimli::Expression<Reader<'a>>,
+) -> Result<Option<u16>, gimli::Error> {
+    let mut operations = expression.operations(unit.encoding());
+    let (
+        Some(gimli::Operation::EntryValue { expression }),
+        Some(gimli::Operation::StackValue),
+        None,
+    ) = (operations.next()?, operations.next()?, operations.next()?)
+    else {
+        return Ok(None);
+    };
+    let mut operations = gimli::Expression(expression).operations(unit.encoding());
+    Ok(match (operations.next()?, operations.next()?) {
+        (Some(gimli::Operation::Register { register }), None) => Some(register.0),
+        _ => None,
+    })
+}
+
+/// Log each operation of an expression that could not be evaluated,
+/// so that the opcode which defeated the evaluator (e.g. DW_OP_entry_value) can be seen.
+fn dw_op_log_handler<'a>(
+    unit: &gimli::UnitRef<Reader<'a>>,
+    attr: &gimli::Attribute<Reader<'a>>,
+    expression: gimli::Expression<Reader<'a>>,
+) -> Result<(), gimli::Error> {
+    let mut operations = expression.operations(unit.encoding());
+    while let Some(operation) = operations.next()? {
+        debug!("   {}:   {:?}", attr.name(), operation);
+    }
+    Ok(())
+}
@@ -1,353 +1,1033 @@
-use fallible_iterator::FallibleIterator;
-use gimli::Reader as _;
-use lazy_static::lazy_static;
-use object::{Object, ObjectSection};
-use serde_json::to_writer_pretty;
-use std::collections::HashMap;
-use std::sync::RwLock;
-use std::{borrow, env, error, fs};
-
-lazy_static! {
-    // The map that stores the subprogram data.
-    static ref SUBPROGRAM_MAP: RwLock<HashMap<String, Subprogram>> = RwLock::new(HashMap::new());
-    static ref CURRENT_SUBPROGRAM: RwLock<Option<String>> = RwLock::new(None);
-}
+use gimli_parser::{CompilationUnit, Demangle, Subprogram};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use std::{env, error, fs, io, mem, ops, path, process, thread};
 
-// This is a simple wrapper around `object::read::RelocationMap` that implements
-// `gimli::read::Relocate` for use with `gimli::RelocateReader`.
-// You only need this if you are parsing relocatable object files.
-#[derive(Debug, Default)]
-struct RelocationMap(object::read::RelocationMap);
+// The serialization format of the output files.
+// Text is a line per subprogram in the manner of `nm`, and Ndjson a compact json object per line and
+// subprogram, which are only written for the units.
+#[derive(Debug, Clone, Copy)]
+enum OutputFormat {
+    Json,
+    Yaml,
+    Text,
+    Ndjson,
+}
 
-impl<'a> gimli::read::Relocate for &'a RelocationMap {
-    fn relocate_address(&self, offset: usize, value: u64) -> gimli::Result<u64> {
-        Ok(self.0.relocate(offset as u64, value))
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = getopts::Options::new();
+    opts.optmulti(
+        "",
+        "input",
+        "input file instead of <file>, which can be repeated to merge the units of several files",
+        "FILE",
+    );
+    opts.optopt(
+        "",
+        "debug-file",
+        "read the DWARF information of <file> from this separate debug file, \
+         instead of the one named by its .gnu_debuglink",
+        "FILE",
+    );
+    opts.optopt(
+        "",
+        "line-out",
+        "output file for the line number table, `-` for stdout",
+        "FILE",
+    );
+    opts.optopt(
+        "",
+        "function",
+        "only write the subprograms whose name or linkage name matches, as an array of subprograms",
+        "NAME",
+    );
+    opts.optopt(
+        "",
+        "source-file",
+        "only write the subprograms declared in this source file, given by its full path or by its \
+         trailing path components, e.g. its file name",
+        "FILE",
+    );
+    opts.optopt(
+        "",
+        "filter",
+        "only write the subprograms whose demangled name, or name without one, matches the regex",
+        "REGEX",
+    );
+    opts.optopt(
+        "",
+        "log-level",
+        "level of the diagnostics on stderr, overriding RUST_LOG (default: warn)",
+        "off|error|warn|info|debug|trace",
+    );
+    opts.optflag(
+        "",
+        "include-declarations",
+        "also write the subprograms that are only declared, not defined",
+    );
+    opts.optflag(
+        "",
+        "globals",
+        "write the global variables, which can be combined with --locals and --params (default: all)",
+    );
+    opts.optflag("", "locals", "write the local variables of the subprograms");
+    opts.optflag("", "params", "write the parameters of the subprograms");
+    opts.optflag(
+        "",
+        "stats",
+        "write the number of units, subprograms, variables, and types instead of the units",
+    );
+    opts.optflag(
+        "",
+        "dedup-types",
+        "merge identical types of all units, and refer to each by the offset of its first copy",
+    );
+    opts.optopt(
+        "",
+        "cu-index",
+        "only parse the unit at this index of each input file, see --list-cus",
+        "N",
+    );
+    opts.optflag(
+        "",
+        "list-cus",
+        "write the index, name, producer, and number of DIEs of each unit instead of parsing them",
+    );
+    opts.optflag(
+        "",
+        "version-info",
+        "write the format, architecture, DWARF versions, and debug sections of each input file \
+         instead of parsing it",
+    );
+    opts.optflag(
+        "",
+        "progress",
+        "show the number of units parsed on stderr, when it is a terminal",
+    );
+    opts.optflag(
+        "",
+        "inline-types",
+        "write the type of each variable expanded with its members, pointees, and elements",
+    );
+    opts.optopt(
+        "",
+        "demangle",
+        "scheme the linkage names are demangled with (default: auto), where auto picks rust or cpp \
+         for each name from its prefix and the language of its unit",
+        "auto|rust|cpp|none",
+    );
+    opts.optflag(
+        "",
+        "no-demangle",
+        "leave the linkage names mangled, as --demangle none",
+    );
+    opts.optflag(
+        "",
+        "append",
+        "merge the units into those of the existing <output>, replacing the units of the same input \
+         file and the subprograms of the same linkage name",
+    );
+    opts.optflag(
+        "",
+        "stream",
+        "write each compilation unit as soon as it is parsed, as compact json",
+    );
+    opts.optopt(
+        "",
+        "format",
+        "serialization format of the output files (default: json), where text writes a \
+         line per subprogram like nm, and ndjson a compact json object per line and subprogram",
+        "json|yaml|text|ndjson",
+    );
+    opts.optopt(
+        "",
+        "max-depth",
+        &format!(
+            "deepest nesting of DIEs and types that is followed (default: {})",
+            gimli_parser::DEFAULT_MAX_DEPTH
+        ),
+        "N",
+    );
+    let matches = match opts.parse(&args[1..]) {
+        Ok(matches) => matches,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit_with_usage(&args[0], &opts);
+        }
+    };
+    // The input files are either given with `--input`, or as the first positional argument.
+    let inputs = if matches.opt_present("input") { 0 } else { 1 };
+    if matches.free.len() < inputs || matches.free.len() > inputs + 1 {
+        exit_with_usage(&args[0], &opts);
     }
-
-    fn relocate_offset(&self, offset: usize, value: usize) -> gimli::Result<usize> {
-        <usize as gimli::ReaderOffset>::from_u64(self.0.relocate(offset as u64, value as u64))
+    // A debug file belongs to a single binary.
+    if matches.opt_present("debug-file") && matches.opt_count("input") > 1 {
+        eprintln!("--debug-file cannot be used with several input files");
+        exit_with_usage(&args[0], &opts);
     }
-}
-
-// The section data that will be stored in `DwarfSections` and `DwarfPackageSections`.
-#[derive(Default)]
-struct Section<'data> {
-    data: borrow::Cow<'data, [u8]>,
-    relocations: RelocationMap,
-}
-
-// The struct that represents a local variable in the stack.
-// var_type is a usize that stands for a DW_TAG_type node.
-// location is a stack offset and is None if the location expression is not `RequiredFrameBase`.
-#[derive(Debug, serde::Serialize)]
-struct Variable {
-    name: String,
-    var_type: usize,
-    location: Option<i64>,
-}
-
-// The struct that represents a function or method.
-// The linkage_name is used as the key in the subprogram map, and it stands for the function name in elf file.
-#[derive(Debug, serde::Serialize)]
-struct Subprogram {
-    name: String,
-    linkage_name: String,
-    ret_type: usize,
-    variables: Vec<Variable>,
-}
+    let format = match matches.opt_str("format").as_deref() {
+        None | Some("json") => OutputFormat::Json,
+        Some("yaml") => OutputFormat::Yaml,
+        Some("text") => OutputFormat::Text,
+        Some("ndjson") => OutputFormat::Ndjson,
+        Some(format) => {
+            eprintln!("Unknown output format: {}", format);
+            exit_with_usage(&args[0], &opts);
+        }
+    };
+    if matches.opt_present("stream") && matches!(format, OutputFormat::Yaml) {
+        eprintln!("--stream only supports the json format");
+        exit_with_usage(&args[0], &opts);
+    }
+    // The text and ndjson formats only describe subprograms, so they have no room for the other outputs.
+    if matches!(format, OutputFormat::Text | OutputFormat::Ndjson) {
+        let conflict = ["stats", "list-cus", "version-info", "append", "line-out"]
+            .into_iter()
+            .find(|name| matches.opt_present(name));
+        if let Some(name) = conflict {
+            eprintln!(
+                "--{} cannot be used with --format {}",
+                name,
+                matches.opt_str("format").unwrap_or_default()
+            );
+            exit_with_usage(&args[0], &opts);
+        }
+    }
+    // The existing units are read back as json, and merged with all the new ones at once.
+    if matches.opt_present("append") {
+        let conflict = ["stream", "stats", "list-cus", "function"]
+            .into_iter()
+            .find(|name| matches.opt_present(name));
+        let error = if let Some(name) = conflict {
+            Some(format!("--append cannot be used with --{}", name))
+        } else if matches!(format, OutputFormat::Yaml) {
+            Some("--append only supports the json format".to_string())
+        } else if matches.free.len() == inputs || matches.free[inputs] == "-" {
+            Some("--append needs an output file".to_string())
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            eprintln!("{}", error);
+            exit_with_usage(&args[0], &opts);
+        }
+    }
+    // The types of a unit can only be merged with those of the units after it once all are parsed.
+    if matches.opt_present("stream") && matches.opt_present("dedup-types") {
+        eprintln!("--dedup-types cannot be used with --stream");
+        exit_with_usage(&args[0], &opts);
+    }
+    let mut options = gimli_parser::ParseOptions {
+        inline_types: matches.opt_present("inline-types"),
+        ..Default::default()
+    };
+    if let Some(index) = matches.opt_str("cu-index") {
+        match index.parse::<usize>() {
+            Ok(index) => options.unit_index = Some(index),
+            Err(_) => {
+                eprintln!("Invalid unit index: {}", index);
+                exit_with_usage(&args[0], &opts);
+            }
+        }
+    }
+    if let Some(depth) = matches.opt_str("max-depth") {
+        match depth.parse::<usize>() {
+            Ok(max_depth) if max_depth > 0 => options.max_depth = max_depth,
+            _ => {
+                eprintln!("Invalid max depth: {}", depth);
+                exit_with_usage(&args[0], &opts);
+            }
+        }
+    }
+    if matches.opt_present("no-demangle") && matches.opt_present("demangle") {
+        eprintln!("--no-demangle cannot be used with --demangle");
+        exit_with_usage(&args[0], &opts);
+    }
+    options.demangle = match matches.opt_str("demangle").as_deref() {
+        None | Some("auto") if !matches.opt_present("no-demangle") => Demangle::Auto,
+        Some("rust") => Demangle::Rust,
+        Some("cpp") => Demangle::Cpp,
+        None | Some("none") => Demangle::None,
+        Some(scheme) => {
+            eprintln!("Unknown demangling scheme: {}", scheme);
+            exit_with_usage(&args[0], &opts);
+        }
+    };
+    // The diagnostics go to stderr, so that stdout only has the output data.
+    let mut logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"));
+    if let Some(level) = matches.opt_str("log-level") {
+        match level.parse::<log::LevelFilter>() {
+            Ok(level) => logger.filter_level(level),
+            Err(_) => {
+                eprintln!("Unknown log level: {}", level);
+                exit_with_usage(&args[0], &opts);
+            }
+        };
+    }
+    logger.init();
 
-// The reader type that will be stored in `Dwarf` and `DwarfPackage`.
-// If you don't need relocations, you can use `gimli::EndianSlice` directly.
-type Reader<'data> =
-    gimli::RelocateReader<gimli::EndianSlice<'data, gimli::RunTimeEndian>, &'data RelocationMap>;
+    let filter = match matches
+        .opt_str("filter")
+        .map(|pattern| regex::Regex::new(&pattern))
+    {
+        None => None,
+        Some(Ok(filter)) => Some(filter),
+        Some(Err(err)) => {
+            eprintln!("Invalid filter regex: {}", err);
+            process::exit(1);
+        }
+    };
 
-fn main() {
-    let mut args = env::args();
-    if args.len() != 4 {
-        println!(
-            "Usage: {} <file> <subprogram.out> <type.out>",
-            args.next().unwrap()
-        );
-        return;
-    }
-    args.next().unwrap();
-    let path = args.next().unwrap();
-    // The output file for the subprogram data, which is a JSON file.
-    // The JSON file contains the name, linkage name, return type, and local variables of each function.
-    let subprogram_out = args.next().unwrap();
-    // The output file for the type data, which is a JSON file.
-    let _type_out = args.next().unwrap();
-
-    let file = fs::File::open(path).unwrap();
-    let mmap = unsafe { memmap2::Mmap::map(&file).unwrap() };
-    let object = object::File::parse(&*mmap).unwrap();
-    let endian = if object.is_little_endian() {
-        gimli::RunTimeEndian::Little
+    let progress = if matches.opt_present("progress") {
+        let progress = Arc::new(gimli_parser::Progress::default());
+        options.progress = Some(progress.clone());
+        let stop = Arc::new(AtomicBool::new(false));
+        Some((stop.clone(), show_progress(progress, stop)))
     } else {
-        gimli::RunTimeEndian::Big
+        None
     };
-
-    dump_file(&object, endian).unwrap();
-
-    let map = SUBPROGRAM_MAP.read().unwrap();
-    let file = fs::File::create(subprogram_out).expect("Unable to create file");
-    to_writer_pretty(file, &*map).expect("Unable to write data");
-    println!("Data successfully written to the output file.");
+    let result = run(&matches, format, filter, &options);
+    if let Some((stop, thread)) = progress {
+        stop.store(true, Ordering::Relaxed);
+        let _ = thread.join();
+    }
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
 }
 
-/// Get the DWARF information from the object file.
-fn dump_file(
-    object: &object::File,
-    endian: gimli::RunTimeEndian,
+/// Parse the input files and write the output files, once the arguments are validated.
+/// The units of several input files are written one file after the other, as from a single file.
+fn run(
+    matches: &getopts::Matches,
+    format: OutputFormat,
+    filter: Option<regex::Regex>,
+    options: &gimli_parser::ParseOptions,
 ) -> Result<(), Box<dyn error::Error>> {
-    // Load a `Section` that may own its data.
-    fn load_section<'data>(
-        object: &object::File<'data>,
-        name: &str,
-    ) -> Result<Section<'data>, Box<dyn error::Error>> {
-        Ok(match object.section_by_name(name) {
-            Some(section) => Section {
-                data: section.uncompressed_data()?,
-                relocations: section.relocation_map().map(RelocationMap)?,
-            },
-            None => Default::default(),
+    let mut paths = matches.opt_strs("input");
+    let mut free = matches.free.iter();
+    if paths.is_empty() {
+        paths.extend(free.next().cloned());
+    }
+    // The output file, `-` or omitted for stdout.
+    // It contains the metadata, subprograms, and types of each compilation unit.
+    let out = free.next().map_or("-", String::as_str);
+
+    // Each subprogram is tagged with its input file, and definitions of the same linkage name in
+    // different files are reported.
+    let mut definitions = Definitions::default();
+    let mut merge = |unit: &mut CompilationUnit, path: &str| {
+        unit.source_file = path.to_string();
+        for subprogram in unit.subprograms.values_mut() {
+            subprogram.source_file = path.to_string();
+        }
+        definitions.add(unit, path);
+    };
+
+    // The filters are applied when writing, so that they compose with the other selections.
+    // Deleted functions only exist as declarations, so they are only kept with `--include-declarations`.
+    // Overloads share a name, so `--function` keeps all the subprograms that match. A name that
+    // matches no subprogram is told apart from one whose subprograms are all dropped by the other filters.
+    // `--filter` matches the demangled name, e.g. `ns::f(int)`, and the name when there is none.
+    // `--source-file` matches whole path components of the declaration file, so `foo.c` matches
+    // `/src/foo.c` but not `/src/barfoo.c`, and drops the subprograms whose file is unknown.
+    let include_declarations = matches.opt_present("include-declarations");
+    let function = matches.opt_str("function");
+    let source_file = matches.opt_str("source-file");
+    let named = |subprogram: &Subprogram| {
+        function.as_ref().is_none_or(|function| {
+            subprogram.name == *function || subprogram.linkage_name == *function
         })
+    };
+    let keep = |subprogram: &Subprogram| {
+        (include_declarations || !subprogram.declaration)
+            && filter.as_ref().is_none_or(|filter| {
+                filter.is_match(
+                    subprogram
+                        .demangled_name
+                        .as_deref()
+                        .unwrap_or(&subprogram.name),
+                )
+            })
+            && named(subprogram)
+            && source_file.as_ref().is_none_or(|source_file| {
+                subprogram
+                    .decl_file
+                    .as_ref()
+                    .is_some_and(|decl_file| path::Path::new(decl_file).ends_with(source_file))
+            })
+    };
+    // `--globals`, `--locals`, and `--params` select the variables that are written, all by default.
+    let (globals, locals, params) = match (
+        matches.opt_present("globals"),
+        matches.opt_present("locals"),
+        matches.opt_present("params"),
+    ) {
+        (false, false, false) => (true, true, true),
+        selected => selected,
+    };
+    let select = |unit: &mut CompilationUnit| {
+        unit.subprograms.retain(|_, subprogram| keep(subprogram));
+        if !globals {
+            unit.globals.clear();
+        }
+        for subprogram in unit.subprograms.values_mut() {
+            subprogram.variables.retain(
+                |variable| {
+                    if variable.parameter {
+                        params
+                    } else {
+                        locals
+                    }
+                },
+            );
+        }
+    };
+    let line_out = matches.opt_str("line-out");
+    let debug_file = matches.opt_str("debug-file");
+    // With `--stats`, only the counts of what passed the filters are written.
+    let stats = matches.opt_present("stats");
+
+    // The files are described from their section tables and unit headers, without parsing any DIE.
+    if matches.opt_present("version-info") {
+        let mut files = Vec::new();
+        for path in &paths {
+            let data = read_input(path)?;
+            let mut file =
+                gimli_parser::file_info(&data).map_err(|err| format!("{}: {}", path, err))?;
+            file.source_file = path.to_string();
+            files.push(file);
+        }
+        write_output(create_output(out)?, "file_info", &files, format)?;
+        return Ok(());
     }
 
-    // Borrow a `Section` to create a `Reader`.
-    fn borrow_section<'data>(
-        section: &'data Section<'data>,
-        endian: gimli::RunTimeEndian,
-    ) -> Reader<'data> {
-        let slice = gimli::EndianSlice::new(borrow::Cow::as_ref(&section.data), endian);
-        gimli::RelocateReader::new(slice, &section.relocations)
+    // The units are listed from their headers and root DIEs, without parsing them.
+    if matches.opt_present("list-cus") {
+        let mut units = Vec::new();
+        for path in &paths {
+            let data = read_input(path)?;
+            let debug_data = read_debug_input(path, &data, debug_file.as_deref())?;
+            let mut file = gimli_parser::list_units(debug_data.as_deref().unwrap_or(&data))
+                .map_err(|err| format!("{}: {}", path, err))?;
+            for unit in &mut file {
+                unit.source_file = path.to_string();
+            }
+            units.append(&mut file);
+        }
+        write_output(create_output(out)?, "unit_list", &units, format)?;
+        return Ok(());
     }
 
-    // Load all of the sections.
-    let dwarf_sections = gimli::DwarfSections::load(|id| load_section(object, id.name()))?;
+    // The subprograms that `--function` matches are written on their own, outside of their units.
+    let text = matches!(format, OutputFormat::Text | OutputFormat::Ndjson);
+    let matched_only = function.is_some() && !stats && !text;
 
-    // Create `Reader`s for all of the sections and do preliminary parsing.
-    // Alternatively, we could have used `Dwarf::load` with an owned type such as `EndianRcSlice`.
-    let dwarf = dwarf_sections.borrow(|section| borrow_section(section, endian));
+    if matches.opt_present("stream") {
+        let mut units = if stats || text || matched_only {
+            None
+        } else {
+            Some(StreamWriter::new(create_output(out)?, "units")?)
+        };
+        let mut text = if text {
+            Some(create_output(out)?)
+        } else {
+            None
+        };
+        let mut counts = Stats::default();
+        let mut lines = match line_out {
+            Some(line_out) => Some(StreamWriter::new(create_output(&line_out)?, "lines")?),
+            None => None,
+        };
+        let mut matched = matched_only.then(Vec::new);
+        let (mut named_found, mut found) = (false, false);
+        for path in &paths {
+            let data = read_input(path)?;
+            let debug_data = read_debug_input(path, &data, debug_file.as_deref())?;
+            gimli_parser::parse_dwarf_streaming_with_debug_file(
+                &data,
+                debug_data.as_deref().unwrap_or(&data),
+                options,
+                |mut unit, unit_lines| {
+                    if let Some(subprogram) = unit
+                        .subprograms
+                        .values()
+                        .find(|subprogram| subprogram.is_main)
+                    {
+                        log::info!("Entry point: {}", subprogram.name);
+                    }
+                    merge(&mut unit, path);
+                    named_found |= unit.subprograms.values().any(named);
+                    select(&mut unit);
+                    found |= !unit.subprograms.is_empty();
+                    counts.add(&unit);
+                    if let Some(units) = &mut units {
+                        units.push(&unit)?;
+                    }
+                    if let Some(text) = &mut text {
+                        write_subprograms(text, &unit, format)?;
+                    }
+                    if let Some(matched) = &mut matched {
+                        matched.extend(mem::take(&mut unit.subprograms).into_values());
+                    }
+                    if let Some(lines) = &mut lines {
+                        for row in &unit_lines {
+                            lines.push(row)?;
+                        }
+                    }
+                    Ok::<_, Box<dyn error::Error>>(())
+                },
+            )
+            .map_err(|err| input_error(path, &*err))?;
+        }
+        match (units, text, matched) {
+            (Some(units), _, _) => units.finish()?,
+            (None, Some(mut text), _) => text.flush()?,
+            (None, None, Some(matched)) if found => {
+                write_matches(create_output(out)?, &matched, format)?
+            }
+            (None, None, Some(_)) => {}
+            (None, None, None) => write_output(create_output(out)?, "stats", &counts, format)?,
+        }
+        if let Some(lines) = lines {
+            lines.finish()?;
+        }
+        // The units are already written, so a missing function is only reported at the end.
+        if let Some(function) = function.as_ref().filter(|_| !found) {
+            return Err(missing_function(function, named_found).into());
+        }
+        eprintln!("Data successfully written to the output file.");
+        return Ok(());
+    }
 
-    // Iterate over the compilation units.
-    // We only need to iterate over the compilation units in the `.debug_info` section.
-    let mut iter = dwarf.units();
-    let debug_info_header = iter
-        .find(|header| Ok(header.offset().as_debug_info_offset().unwrap().0 == 0))
-        .expect("No .debug_info header found")
-        .unwrap();
+    // Type offsets are only unique within a file, so the types of each file are merged on their own.
+    let dedup_types = matches.opt_present("dedup-types");
+    let mut collapsed = 0;
+    let mut parsed = gimli_parser::ParseOutput::default();
+    for path in &paths {
+        let data = read_input(path)?;
+        let debug_data = read_debug_input(path, &data, debug_file.as_deref())?;
+        let mut file = gimli_parser::parse_dwarf_with_debug_file(
+            &data,
+            debug_data.as_deref().unwrap_or(&data),
+            options,
+        )
+        .map_err(|err| input_error(path, &err))?;
+        if let Some(subprogram) = file.main_subprogram() {
+            log::info!("Entry point: {}", subprogram.name);
+        }
+        if dedup_types {
+            collapsed += file.dedup_types();
+        }
+        for unit in &mut file.units {
+            merge(unit, path);
+        }
+        parsed.units.append(&mut file.units);
+        parsed.lines.append(&mut file.lines);
+    }
+    if dedup_types {
+        log::info!("Collapsed {} duplicate types", collapsed);
+    }
+    let named_found = parsed.subprograms().any(named);
+    for unit in &mut parsed.units {
+        select(unit);
+    }
+    if let Some(function) = &function {
+        if parsed.subprograms().next().is_none() {
+            return Err(missing_function(function, named_found).into());
+        }
+    }
+    if stats {
+        let mut counts = Stats::default();
+        for unit in &parsed.units {
+            counts.add(unit);
+        }
+        write_output(create_output(out)?, "stats", &counts, format)?;
+    } else if matches.opt_present("append") {
+        let units = append_units(out, &parsed.units)?;
+        write_output(create_output(out)?, "units", &units, format)?;
+    } else if text {
+        let mut writer = create_output(out)?;
+        for unit in &parsed.units {
+            write_subprograms(&mut writer, unit, format)?;
+        }
+        writer.flush()?;
+    } else if matched_only {
+        let matched: Vec<&Subprogram> = parsed.subprograms().collect();
+        write_matches(create_output(out)?, &matched, format)?;
+    } else {
+        write_output(create_output(out)?, "units", &parsed.units, format)?;
+    }
+    if let Some(line_out) = line_out {
+        write_output(create_output(&line_out)?, "lines", &parsed.lines, format)?;
+    }
+    eprintln!("Data successfully written to the output file.");
+    Ok(())
+}
 
-    let unit = dwarf.unit(debug_info_header)?;
-    let unit_ref = unit.unit_ref(&dwarf);
-    dump_unit(unit_ref)?;
+// Print the usage to stderr after an invalid argument, and exit with the status of a usage error,
+// so that scripts can tell the arguments were rejected.
+// The error of a `--function` that selects no subprogram, where `named` is true if some subprograms
+// have the name but are all dropped by the other filters, e.g. because they are only declarations.
+fn missing_function(function: &str, named: bool) -> String {
+    if named {
+        format!("No subprogram named {} passed the filters", function)
+    } else {
+        format!("No subprogram named {}", function)
+    }
+}
 
-    Ok(())
+fn exit_with_usage(program: &str, opts: &getopts::Options) -> ! {
+    let brief = format!(
+        "Usage: {0} [options] <file> [<output>]\n       {0} [options] --input <file>... [<output>]",
+        program
+    );
+    eprint!("{}", opts.usage(&brief));
+    process::exit(2);
 }
 
-/// Iterate over the Debugging Information Entries (DIEs) in the unit.
-fn dump_unit(unit: gimli::UnitRef<Reader>) -> Result<(), gimli::Error> {
-    // Iterate over the Debugging Information Entries (DIEs) in the unit.
-    let mut depth = 0;
-    let mut entries = unit.entries();
-    while let Some((delta_depth, entry)) = entries.next_dfs()? {
-        depth += delta_depth;
+// The contents of an input file, which is mapped into memory if possible.
+enum Input {
+    Mapped(memmap2::Mmap),
+    Read(Vec<u8>),
+}
 
-        println!("<{}><{}> {}", depth, entry.offset().0, entry.tag());
+impl ops::Deref for Input {
+    type Target = [u8];
 
-        match entry.tag() {
-            gimli::DW_TAG_subprogram => dw_tag_subprogram_handler(&unit, &entry)?,
-            gimli::DW_TAG_variable => dw_tag_variable_handler(&unit, &entry)?,
-            _ => dw_tag_default_handler(&unit, &entry)?,
+    fn deref(&self) -> &[u8] {
+        match self {
+            Input::Mapped(mmap) => mmap,
+            Input::Read(buffer) => buffer,
         }
     }
-    Ok(())
 }
 
-/// Handler for DW_TAG_subprogram, which is a function or method.
-/// we are interested in the name, linkage name, and return type of the function.
-fn dw_tag_subprogram_handler<'a>(
-    unit: &gimli::UnitRef<Reader<'a>>,
-    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
-) -> Result<(), gimli::Error> {
-    let mut name = String::new();
-    let mut linkage_name = String::new();
-    let mut ret_type = 0;
-
-    let mut attrs = entry.attrs();
-    while let Some(attr) = attrs.next()? {
-        match attr.name() {
-            gimli::DW_AT_name => {
-                name = dw_at_name_handler(&unit, &attr)?;
-                println!("   {}: {:?}", attr.name(), name);
-            }
-            gimli::DW_AT_linkage_name => {
-                linkage_name = dw_at_name_handler(&unit, &attr)?;
-                println!("   {}: {:?}", attr.name(), linkage_name);
-            }
-            gimli::DW_AT_type => {
-                println!("   {}: {:?}", attr.name(), dw_at_type_handler(&attr)?);
-                ret_type = dw_at_type_handler(&attr)?;
-            }
-            _ => {
-                // println!("   {}: Unparsed Attribute", attr.name());
-                continue;
-            }
+/// Map an input file into memory.
+/// Some files cannot be mapped, e.g. pipes, files in /proc, and some network mounts,
+/// so they are read into memory instead.
+fn read_input(path: &str) -> Result<Input, String> {
+    let file = fs::File::open(path).map_err(|err| format!("Unable to open {}: {}", path, err))?;
+    match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(mmap) => Ok(Input::Mapped(mmap)),
+        Err(err) => {
+            log::debug!("Unable to map {}, reading it instead: {}", path, err);
+            // The open file is read rather than the path, which a pipe cannot be opened again at.
+            let mut buffer = Vec::new();
+            io::Read::read_to_end(&mut &file, &mut buffer)
+                .map_err(|err| format!("Unable to read {}: {}", path, err))?;
+            Ok(Input::Read(buffer))
         }
     }
+}
 
-    // Insert the subprogram data into the map.
-    let mut map = SUBPROGRAM_MAP.write().unwrap();
-    map.insert(
-        linkage_name.clone(),
-        Subprogram {
-            name,
-            linkage_name: linkage_name.clone(),
-            ret_type,
-            variables: Vec::new(),
-        },
-    );
+/// Prefix an error with the input file at `path`, and point at the options that help with a file
+/// without debug info.
+fn input_error(path: &str, err: &(dyn error::Error + 'static)) -> String {
+    match err.downcast_ref::<gimli_parser::ParseError>() {
+        Some(gimli_parser::ParseError::NoDebugInfo) => format!(
+            "{}: {}, it may be stripped (see --debug-file, or --version-info for its sections)",
+            path, err
+        ),
+        _ => format!("{}: {}", path, err),
+    }
+}
 
-    // Update the current subprogram.
-    let mut current_subprogram = CURRENT_SUBPROGRAM.write().unwrap();
-    *current_subprogram = Some(linkage_name.clone());
+/// Read the separate debug file of the input file at `path`, which is either given with `--debug-file`,
+/// or named by the `.gnu_debuglink` of the input and looked up where GDB does: next to the input, in
+/// its `.debug` directory, and under `/usr/lib/debug`. The input is parsed itself if there is none.
+fn read_debug_input(
+    path: &str,
+    data: &[u8],
+    debug_file: Option<&str>,
+) -> Result<Option<Input>, Box<dyn error::Error>> {
+    let link = gimli_parser::debug_link(data).map_err(|err| format!("{}: {}", path, err))?;
+    if let Some(debug_file) = debug_file {
+        let debug_data = read_input(debug_file)?;
+        if link.is_some_and(|link| !link.matches(&debug_data)) {
+            log::warn!(
+                "{} does not match the CRC in the .gnu_debuglink of {}",
+                debug_file,
+                path
+            );
+        }
+        return Ok(Some(debug_data));
+    }
+    let Some(link) = link else {
+        return Ok(None);
+    };
 
-    Ok(())
+    let dir = path::Path::new(path)
+        .parent()
+        .unwrap_or(path::Path::new(""));
+    let mut candidates = vec![
+        dir.join(&link.file_name),
+        dir.join(".debug").join(&link.file_name),
+    ];
+    if let Ok(dir) = fs::canonicalize(dir) {
+        let global = path::Path::new("/usr/lib/debug").join(dir.strip_prefix("/").unwrap_or(&dir));
+        candidates.push(global.join(&link.file_name));
+    }
+    for candidate in candidates {
+        // The input may link to a file of its own name in another directory, but never to itself.
+        if !candidate.is_file() || fs::canonicalize(&candidate).ok() == fs::canonicalize(path).ok()
+        {
+            continue;
+        }
+        let candidate = candidate.to_string_lossy();
+        let debug_data = read_input(&candidate)?;
+        if link.matches(&debug_data) {
+            log::info!(
+                "Reading the debug information of {} from {}",
+                path,
+                candidate
+            );
+            return Ok(Some(debug_data));
+        }
+        log::warn!(
+            "{} does not match the CRC in the .gnu_debuglink of {}, skipping it",
+            candidate,
+            path
+        );
+    }
+    log::warn!(
+        "Debug file {} of {} not found, reading {} itself",
+        link.file_name,
+        path,
+        path
+    );
+    Ok(None)
 }
 
-/// Handler for DW_TAG_variable, which is a local variable.
-/// we are interested in the name, type, and location(stack offset) of the variable.
-fn dw_tag_variable_handler<'a>(
-    unit: &gimli::UnitRef<Reader<'a>>,
-    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
-) -> Result<(), gimli::Error> {
-    let mut name = String::new();
-    let mut var_type = 0;
-    let mut location = None;
-
-    let mut attrs = entry.attrs();
-    while let Some(attr) = attrs.next()? {
-        match attr.name() {
-            gimli::DW_AT_name => {
-                name = dw_at_name_handler(&unit, &attr)?;
-                println!("   {}: {:?}", attr.name(), name);
-            }
-            gimli::DW_AT_type => {
-                var_type = dw_at_type_handler(&attr)?;
-                println!("   {}: {:?}", attr.name(), var_type);
-            }
-            gimli::DW_AT_location => {
-                location = dw_at_location_handler(&unit, &attr)?;
-            }
-            _ => {
-                // println!("   {}: Unparsed Attribute", attr.name());
-                continue;
+// The definitions of the linkage names seen so far, with the input file and the declaration file of
+// the first one, to report the same linkage name defined from different sources in several files.
+#[derive(Default)]
+struct Definitions(HashMap<String, (String, Option<String>)>);
+
+impl Definitions {
+    /// Record the subprograms with code of a unit of the input file at `path`.
+    /// Inline functions and templates are defined in every file that uses them, from the same
+    /// declaration file, so only a definition from another declaration file is a conflict.
+    fn add(&mut self, unit: &CompilationUnit, path: &str) {
+        let defined = unit.subprograms.values().filter(|subprogram| {
+            !subprogram.linkage_name.is_empty() && !subprogram.ranges.is_empty()
+        });
+        for subprogram in defined {
+            let (first_path, first_file) = self
+                .0
+                .entry(subprogram.linkage_name.clone())
+                .or_insert_with(|| (path.to_string(), subprogram.decl_file.clone()));
+            if first_path != path && *first_file != subprogram.decl_file {
+                log::warn!(
+                    "Conflicting definitions of {}: from {:?} in {}, and from {:?} in {}",
+                    subprogram.linkage_name,
+                    first_file,
+                    first_path,
+                    subprogram.decl_file,
+                    path
+                );
             }
         }
     }
+}
 
-    // The current subprogram is the key in the subprogram map.
-    // If the current subprogram is None, which stand for a global variable, we just ignore it.
-    let linkage_name = {
-        let current_subprogram = CURRENT_SUBPROGRAM.read().unwrap();
-        match &*current_subprogram {
-            Some(name) => name.clone(),
-            None => {
-                return Ok(());
+/// Merge the units into those of the existing output file at `path` for `--append`, which is created
+/// if it does not exist yet. The units of an input file that is parsed again are replaced, and so are
+/// the subprograms of the other units that are defined again under the same linkage name, so that the
+/// output stays an index of the latest definition of each function.
+fn append_units(
+    path: &str,
+    units: &[CompilationUnit],
+) -> Result<Vec<serde_json::Value>, Box<dyn error::Error>> {
+    let mut existing = match fs::File::open(path) {
+        Ok(file) => {
+            let output: serde_json::Value = serde_json::from_reader(io::BufReader::new(file))
+                .map_err(|err| format!("Unable to read {}: {}", path, err))?;
+            let version = &output["schema_version"];
+            if version.as_u64() != Some(u64::from(gimli_parser::SCHEMA_VERSION)) {
+                return Err(format!(
+                    "Unable to append to {}: its schema version is {}, not {}",
+                    path,
+                    version,
+                    gimli_parser::SCHEMA_VERSION
+                )
+                .into());
+            }
+            match output.get("units") {
+                Some(serde_json::Value::Array(units)) => units.clone(),
+                _ => return Err(format!("Unable to append to {}: no units", path).into()),
             }
         }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(format!("Unable to open {}: {}", path, err).into()),
     };
 
-    let mut map = SUBPROGRAM_MAP.write().unwrap();
-    if let Some(subprogram) = map.get_mut(&linkage_name) {
-        subprogram.variables.push(Variable {
-            name,
-            var_type,
-            location,
-        });
+    let sources: HashSet<&str> = units.iter().map(|unit| unit.source_file.as_str()).collect();
+    let defined: HashSet<&str> = units
+        .iter()
+        .flat_map(|unit| unit.subprograms.values())
+        .map(|subprogram| subprogram.linkage_name.as_str())
+        .filter(|linkage_name| !linkage_name.is_empty())
+        .collect();
+    let count = existing.len();
+    existing.retain(|unit| {
+        let source_file = unit.get("source_file").and_then(|v| v.as_str());
+        !source_file.is_some_and(|source_file| sources.contains(source_file))
+    });
+    let mut replaced = 0;
+    for unit in &mut existing {
+        if let Some(subprograms) = unit.get_mut("subprograms").and_then(|v| v.as_object_mut()) {
+            let before = subprograms.len();
+            subprograms.retain(|_, subprogram| {
+                let linkage_name = subprogram.get("linkage_name").and_then(|v| v.as_str());
+                !linkage_name.is_some_and(|linkage_name| defined.contains(linkage_name))
+            });
+            replaced += before - subprograms.len();
+        }
     }
+    log::info!(
+        "Appending {} units to {}, replacing {} units and {} subprograms",
+        units.len(),
+        path,
+        count - existing.len(),
+        replaced
+    );
 
+    for unit in units {
+        existing.push(serde_json::to_value(unit)?);
+    }
+    Ok(existing)
+}
+
+/// Open an output file, where `-` stands for stdout.
+fn create_output(path: &str) -> Result<Box<dyn io::Write>, String> {
+    if path == "-" {
+        Ok(Box::new(io::stdout()))
+    } else {
+        let file =
+            fs::File::create(path).map_err(|err| format!("Unable to create {}: {}", path, err))?;
+        Ok(Box::new(io::BufWriter::new(file)))
+    }
+}
+
+// The top-level object of an output file, `{ "schema_version": SCHEMA_VERSION, "<key>": <data> }`.
+struct VersionedOutput<'a, T> {
+    key: &'static str,
+    data: &'a T,
+}
+
+impl<T: serde::Serialize> serde::Serialize for VersionedOutput<'_, T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("schema_version", &gimli_parser::SCHEMA_VERSION)?;
+        map.serialize_entry(self.key, self.data)?;
+        map.end()
+    }
+}
+
+/// Serialize the data under `key` to the writer in the requested format, along with the schema version.
+fn write_output<T: serde::Serialize>(
+    mut writer: Box<dyn io::Write>,
+    key: &'static str,
+    data: &T,
+    format: OutputFormat,
+) -> Result<(), Box<dyn error::Error>> {
+    let output = VersionedOutput { key, data };
+    match format {
+        OutputFormat::Json => serde_json::to_writer_pretty(&mut writer, &output)?,
+        OutputFormat::Yaml => serde_yaml::to_writer(&mut writer, &output)?,
+        OutputFormat::Text | OutputFormat::Ndjson => {
+            unreachable!("the text and ndjson formats are written by write_subprograms")
+        }
+    }
+    writer.flush()?;
     Ok(())
 }
 
-/// Handler for other DW_TAG_*, which is currently not parsed.
-/// we just print all the attributes.
-fn dw_tag_default_handler<'a>(
-    unit: &gimli::UnitRef<Reader<'a>>,
-    entry: &gimli::DebuggingInformationEntry<Reader<'a>>,
-) -> Result<(), gimli::Error> {
-    let mut attrs = entry.attrs();
-    while let Some(attr) = attrs.next()? {
-        println!(
-            "   {}: {:?}",
-            attr.name(),
-            dw_at_name_handler(&unit, &attr)?
-        );
+/// Serialize the subprograms that `--function` matches to the writer in the requested format, as a
+/// top-level array, e.g. the overloads of a C++ function.
+fn write_matches<T: serde::Serialize>(
+    mut writer: Box<dyn io::Write>,
+    matched: &[T],
+    format: OutputFormat,
+) -> Result<(), Box<dyn error::Error>> {
+    match format {
+        OutputFormat::Json => serde_json::to_writer_pretty(&mut writer, matched)?,
+        OutputFormat::Yaml => serde_yaml::to_writer(&mut writer, matched)?,
+        OutputFormat::Text | OutputFormat::Ndjson => {
+            unreachable!("the text and ndjson formats are written by write_subprograms")
+        }
     }
+    writer.flush()?;
     Ok(())
 }
 
-/// Handler for DW_AT_name, which is a string attribute.
-/// we convert the attribute value from a DebugStrRef(offset) to a string.
-fn dw_at_name_handler<'a>(
-    unit: &gimli::UnitRef<Reader<'a>>,
-    attr: &gimli::Attribute<Reader<'a>>,
-) -> Result<String, gimli::Error> {
-    match unit.attr_string(attr.value()) {
-        Ok(string) => Ok(string.to_string_lossy()?.to_string()),
-        Err(_) => Ok(format!("{:?}", attr.value())),
+/// Write the subprograms of the unit in a format with a line per subprogram, and flush them, so that
+/// a consumer of the streamed units can process each unit as soon as it is parsed.
+fn write_subprograms(
+    writer: &mut dyn io::Write,
+    unit: &CompilationUnit,
+    format: OutputFormat,
+) -> Result<(), Box<dyn error::Error>> {
+    match format {
+        OutputFormat::Text => write_text(writer, unit)?,
+        OutputFormat::Ndjson => write_ndjson(writer, unit)?,
+        OutputFormat::Json | OutputFormat::Yaml => {
+            unreachable!("{:?} is written by write_output", format)
+        }
     }
+    writer.flush()?;
+    Ok(())
 }
 
-/// Handler for DW_AT_type, which is a reference to another DW_TAG_type.
-/// we convert the attribute value from a UnitRef(offset) to a usize, which stands for a DW_TAG_type node.
-fn dw_at_type_handler<'a>(attr: &gimli::Attribute<Reader<'a>>) -> Result<usize, gimli::Error> {
-    if let gimli::AttributeValue::UnitRef(offset) = attr.value() {
-        Ok(offset.0)
-    } else {
-        Err(gimli::Error::UnsupportedOffset)
+/// Write each subprogram of the unit for `--format ndjson`, as compact json followed by a newline.
+/// The lines have no schema version, as they are the serialized `Subprogram` alone.
+fn write_ndjson(writer: &mut dyn io::Write, unit: &CompilationUnit) -> serde_json::Result<()> {
+    for subprogram in unit.subprograms.values() {
+        serde_json::to_writer(&mut *writer, subprogram)?;
+        writer.write_all(b"\n").map_err(serde_json::Error::io)?;
     }
+    Ok(())
 }
 
-/// Handler for DW_AT_location, which is a location expression.
-/// we evaluate the expression and print the result.
-fn dw_at_location_handler(
-    unit: &gimli::Unit<Reader>,
-    attr: &gimli::Attribute<Reader>,
-) -> Result<Option<i64>, gimli::Error> {
-    let expression = attr.exprloc_value().unwrap();
-    let mut eval = expression.evaluation(unit.encoding());
-    let mut result = eval.evaluate().unwrap();
-    loop {
-        match result {
-            // When calculation is complete, print the result.
-            gimli::EvaluationResult::Complete => {
-                let value = eval
-                    .value_result()
-                    .unwrap()
-                    .convert(gimli::ValueType::I64, 0xFFFFFFFFFFFFFFFF)
-                    .unwrap();
-                match value {
-                    gimli::Value::I64(val) => {
-                        println!("   {}: {:?}", attr.name(), val);
-                        return Ok(Some(val));
-                    }
-                    _ => {
-                        println!("   {}: {:?}", attr.name(), value);
-                        return Ok(None);
-                    }
+/// Write a line per subprogram of the unit for `--format text`, in the manner of `nm`:
+/// `<low_pc> <T/t> <demangled_name> (<param_count> params, <local_count> locals)`, where `T` is an
+/// external function and `t` one local to its unit. Subprograms without code have a blank address,
+/// declarations are marked `U`, as undefined symbols are, and subprograms without a demangled name are
+/// written with their name.
+fn write_text(writer: &mut dyn io::Write, unit: &CompilationUnit) -> io::Result<()> {
+    for subprogram in unit.subprograms.values() {
+        let address = match subprogram.ranges.first() {
+            Some(&(low_pc, _)) => format!("{:016x}", low_pc),
+            None => " ".repeat(16),
+        };
+        let kind = if subprogram.declaration {
+            'U'
+        } else if subprogram.external {
+            'T'
+        } else {
+            't'
+        };
+        let params = subprogram
+            .variables
+            .iter()
+            .filter(|variable| variable.parameter)
+            .count();
+        let locals = subprogram.variables.len() - params;
+        writeln!(
+            writer,
+            "{} {} {} ({} params, {} locals)",
+            address,
+            kind,
+            subprogram
+                .demangled_name
+                .as_deref()
+                .unwrap_or(&subprogram.name),
+            params,
+            locals
+        )?;
+    }
+    Ok(())
+}
+
+/// Show the progress bar of `--progress` on stderr, e.g. `[##########          ] 1234/2468 units (50%)`,
+/// with a thread that copies the counts of the parser to it until `stop` is set, and then erases it.
+/// indicatif hides the bar when stderr is not a terminal, where it would only garble a log file.
+fn show_progress(
+    progress: Arc<gimli_parser::Progress>,
+    stop: Arc<AtomicBool>,
+) -> thread::JoinHandle<()> {
+    let bar = indicatif::ProgressBar::new(0);
+    bar.set_style(
+        indicatif::ProgressStyle::with_template("[{bar:40}] {pos}/{len} units ({percent}%)")
+            .expect("the progress template is valid")
+            .progress_chars("## "),
+    );
+    thread::spawn(move || {
+        while !stop.load(Ordering::Relaxed) {
+            let (done, total) = progress.get();
+            bar.set_length(total as u64);
+            bar.set_position(done as u64);
+            thread::sleep(Duration::from_millis(100));
+        }
+        bar.finish_and_clear();
+    })
+}
+
+// The counts written by `--stats`, e.g. to compare two builds of a program.
+#[derive(Debug, Default, serde::Serialize)]
+struct Stats {
+    units: usize,
+    subprograms: usize,
+    parameters: usize,
+    locals: usize,
+    globals: usize,
+    types: usize,
+}
+
+impl Stats {
+    /// Count the subprograms, variables, and types of a unit.
+    fn add(&mut self, unit: &CompilationUnit) {
+        self.units += 1;
+        self.subprograms += unit.subprograms.len();
+        for subprogram in unit.subprograms.values() {
+            for variable in &subprogram.variables {
+                if variable.parameter {
+                    self.parameters += 1;
+                } else {
+                    self.locals += 1;
                 }
             }
-            // We currently only care about the RequiresFrameBase Expression.
-            // Set the frame base to 0 to calculate the offset.
-            gimli::EvaluationResult::RequiresFrameBase => {
-                result = eval.resume_with_frame_base(0).unwrap();
-            }
-            // Unparsed Expression, print the result and break.
-            _ => {
-                println!("   {}: Unparsed Expression: {:?}", attr.name(), result);
-                return Ok(None);
-            }
         }
+        self.globals += unit.globals.len();
+        self.types += unit.types.len();
+    }
+}
+
+// The writer of `--stream`, which frames the same top-level object as `VersionedOutput` by hand
+// and serializes the items of its array one at a time, so that they never are all in memory.
+struct StreamWriter {
+    writer: Box<dyn io::Write>,
+    first: bool,
+}
+
+impl StreamWriter {
+    /// Write the start of `{ "schema_version": SCHEMA_VERSION, "<key>": [`.
+    fn new(mut writer: Box<dyn io::Write>, key: &str) -> Result<Self, Box<dyn error::Error>> {
+        write!(
+            writer,
+            "{{\"schema_version\":{},{}:[",
+            gimli_parser::SCHEMA_VERSION,
+            serde_json::to_string(key)?
+        )?;
+        Ok(StreamWriter {
+            writer,
+            first: true,
+        })
+    }
+
+    /// Write the next item of the array.
+    fn push<T: serde::Serialize>(&mut self, item: &T) -> Result<(), Box<dyn error::Error>> {
+        if !self.first {
+            self.writer.write_all(b",")?;
+        }
+        self.first = false;
+        serde_json::to_writer(&mut self.writer, item)?;
+        Ok(())
+    }
+
+    /// Close the array and the top-level object.
+    fn finish(mut self) -> Result<(), Box<dyn error::Error>> {
+        self.writer.write_all(b"]}\n")?;
+        self.writer.flush()?;
+        Ok(())
     }
 }
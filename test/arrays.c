@@ -0,0 +1,34 @@
+// The C fixture of tests/test_c.rs.
+
+struct message {
+    int length;
+    char data[];
+};
+
+int grid[3][4];
+struct message *last_message;
+
+int sum(int rows, int columns) {
+    int matrix[rows][columns];
+    int total = 0;
+    for (int row = 0; row < rows; row++) {
+        for (int column = 0; column < columns; column++) {
+            matrix[row][column] = grid[row % 3][column % 4];
+            total += matrix[row][column];
+        }
+    }
+    return total;
+}
+
+int count(int step) {
+    static int calls;
+    if (step < 0)
+        goto done;
+    calls += step;
+done:
+    return calls;
+}
+
+static int twice(int value) { return 2 * value; }
+
+int main(void) { return sum(3, 4) + count(twice(1)); }
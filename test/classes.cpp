@@ -0,0 +1,48 @@
+// The C++ fixture of tests/test_cpp.rs.
+
+class Account {
+    int id;
+
+public:
+    long balance;
+
+protected:
+    char kind;
+};
+
+struct Point {
+    int x;
+
+private:
+    int y;
+};
+
+union Value {
+    int integer;
+    float real;
+};
+
+Account account;
+Point point;
+Value value;
+decltype(nullptr) null_pointer;
+void *opaque;
+
+namespace geometry {
+class Shape {
+public:
+    int area() const;
+    int sides() const { return 4; }
+};
+
+int Shape::area() const { return sides() * 2; }
+} // namespace geometry
+
+geometry::Shape shape;
+
+void reset(Point *target) { target->x = 0; }
+
+int main() {
+    reset(&point);
+    return shape.area();
+}
@@ -0,0 +1,26 @@
+// A `no_core` library for the object format fixtures, which cross-compiles without the
+// standard library of the target. Rebuild them with `make fixtures`.
+
+#![feature(no_core, lang_items)]
+#![no_core]
+#![allow(internal_features)]
+
+#[lang = "sized"]
+trait Sized: MetaSized {}
+#[lang = "copy"]
+trait Copy {}
+impl Copy for i32 {}
+#[lang = "pointee_sized"]
+trait PointeeSized {}
+#[lang = "meta_sized"]
+trait MetaSized: PointeeSized {}
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[no_mangle]
+pub extern "C" fn origin_distance(point: &Point) -> i32 {
+    point.x
+}
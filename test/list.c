@@ -0,0 +1,10 @@
+// The self-referential fixture of tests/test_c.rs.
+
+struct Node {
+    struct Node *next;
+    int value;
+};
+
+struct Node head;
+
+int main(void) { return head.next ? head.next->value : head.value; }
@@ -0,0 +1,74 @@
+// Parse the checked-in Mach-O and PE fixtures, built from `test/formats.rs` with DWARF 5, and
+// check that the DWARF sections are found under the section names of each format.
+//
+// Mach-O names the sections `__debug_info` etc. and truncates them to 16 bytes, e.g.
+// `__debug_str_offs`, and PE uses long section names from the COFF string table.
+
+use gimli_parser::{ParseOutput, TypeKind};
+use std::fs;
+use std::path::PathBuf;
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("test")
+        .join(name);
+    fs::read(&path).unwrap()
+}
+
+fn parse_fixture(name: &str) -> ParseOutput {
+    gimli_parser::parse_dwarf(&read_fixture(name)).unwrap()
+}
+
+// Describe the fixture without parsing it, and check that it has the section under its name.
+fn check_file_info(name: &str, format: &str, section: &str) {
+    let info = gimli_parser::file_info(&read_fixture(name)).unwrap();
+    assert_eq!(info.format, format);
+    assert_eq!(info.architecture, "X86_64");
+    assert!(info.debug_info);
+    assert_eq!(info.units, 1);
+    assert_eq!(info.versions, [5]);
+    assert!(!info.split_dwarf);
+    assert!(info.sections.iter().any(|info| info.name == section));
+}
+
+fn check_fixture(output: &ParseOutput) {
+    let [unit] = output.units.as_slice() else {
+        panic!("expected a single unit, found {}", output.units.len());
+    };
+    assert_eq!(unit.version, 5);
+
+    // The names are read through `.debug_str_offsets`.
+    let subprogram = &unit.subprograms["formats::origin_distance"];
+    assert!(!subprogram.ranges.is_empty());
+    let [point] = subprogram.variables.as_slice() else {
+        panic!("expected a single parameter");
+    };
+    assert_eq!(point.name, "point");
+    assert!(point.parameter);
+
+    let pointer = &unit.types[&point.var_type];
+    assert_eq!(pointer.kind, TypeKind::Pointer);
+    let structure = &unit.types[&pointer.inner_type];
+    assert_eq!(structure.kind, TypeKind::Struct);
+    assert_eq!(structure.name, "formats::Point");
+    assert_eq!(structure.byte_size, Some(8));
+    let members: Vec<_> = structure
+        .members
+        .iter()
+        .map(|member| (member.name.as_str(), member.offset.unwrap()))
+        .collect();
+    assert_eq!(members, [("x", 0), ("y", 4)]);
+}
+
+#[test]
+fn test_macho() {
+    check_fixture(&parse_fixture("formats.macho.o"));
+    check_file_info("formats.macho.o", "MachO", "__debug_str_offs");
+}
+
+// A COFF object has relocations that are not supported yet, so the PE fixture is linked.
+#[test]
+fn test_pe() {
+    check_fixture(&parse_fixture("formats.pe.exe"));
+    check_file_info("formats.pe.exe", "Pe", ".debug_str_offsets");
+}
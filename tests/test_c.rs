@@ -0,0 +1,252 @@
+// Compile `test/arrays.c` with debug info, parse it, and check its array types, static locals, linkage,
+// and file table, and `test/list.c` for the types that refer to themselves.
+//
+// The fixture is compiled with `cc` from the PATH, or with `$CC` when set.
+
+use gimli_parser::{Dimension, Location, ParseOptions, ParseOutput, StorageClass, Type, TypeKind};
+use object::{Object, ObjectSymbol};
+use std::path::PathBuf;
+use std::process::Command;
+use std::{env, fs};
+
+// Compile a fixture of `test/` into the temporary directory of the tests, and read the binary.
+fn compile(source: &str, name: &str, flags: &[&str]) -> Vec<u8> {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("test")
+        .join(source);
+    let binary = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .arg("-g")
+        .args(flags)
+        .arg(&fixture)
+        .arg("-o")
+        .arg(&binary)
+        .status()
+        .expect("failed to run cc");
+    assert!(status.success(), "failed to compile {}", fixture.display());
+    fs::read(binary).unwrap()
+}
+
+fn parse_fixture(name: &str, flags: &[&str]) -> ParseOutput {
+    gimli_parser::parse_dwarf(&compile("arrays.c", name, flags)).unwrap()
+}
+
+fn arrays(output: &ParseOutput) -> impl Iterator<Item = &Type> {
+    output
+        .units
+        .iter()
+        .flat_map(|unit| unit.types.values())
+        .filter(|node| node.kind == TypeKind::Array)
+}
+
+// The variable-length array `matrix` has two dimensions computed at run time.
+fn dynamic_dimensions(output: &ParseOutput) -> Vec<Dimension> {
+    let matrix = output
+        .find_subprograms("sum")
+        .into_iter()
+        .flat_map(|subprogram| &subprogram.variables)
+        .find(|variable| variable.name == "matrix")
+        .expect("matrix is not found");
+    assert_eq!(matrix.type_name.as_deref(), Some("int[*][*]"));
+    let node = output
+        .units
+        .iter()
+        .find_map(|unit| unit.types.get(&matrix.var_type))
+        .unwrap();
+    assert_eq!(node.count, None);
+    node.dimensions.clone()
+}
+
+// The function-local `static` is stored at an absolute address, and still belongs to `count`.
+fn check_static_local(output: &ParseOutput) {
+    let calls = output
+        .find_subprograms("count")
+        .into_iter()
+        .flat_map(|subprogram| &subprogram.variables)
+        .find(|variable| variable.name == "calls")
+        .expect("calls is not found");
+    assert_eq!(calls.storage_class, Some(StorageClass::Static));
+    assert!(matches!(
+        calls.location[0].location,
+        Some(Location::Address(address)) if address != 0
+    ));
+    assert!(!output.units[0]
+        .globals
+        .iter()
+        .any(|global| global.name == "calls"));
+}
+
+// The file index of every declaration refers to its path in the file table of the unit.
+fn check_file_table(output: &ParseOutput) {
+    let unit = &output.units[0];
+    let main = &output.find_subprograms("main")[0];
+    let index = main.decl_file_index.expect("main has no file index") as usize;
+    assert!(unit.files[index].ends_with("test/arrays.c"));
+    for subprogram in unit.subprograms.values() {
+        let path = subprogram
+            .decl_file_index
+            .map(|index| &unit.files[index as usize]);
+        assert_eq!(path, subprogram.decl_file.as_ref());
+    }
+}
+
+#[test]
+fn test_c_arrays() {
+    let output = parse_fixture("arrays", &["-O0"]);
+
+    let grid = arrays(&output)
+        .find(|node| node.count.is_some())
+        .expect("grid is not found");
+    assert_eq!(grid.dimensions, [Dimension::Count(3), Dimension::Count(4)]);
+    assert_eq!(grid.count, Some(12));
+
+    // The flexible array member has no count at all.
+    assert!(arrays(&output).any(|node| node.dimensions == [Dimension::Unbounded]));
+
+    // Without optimizations, the bounds are location expressions that read the stack.
+    assert_eq!(
+        dynamic_dimensions(&output),
+        [Dimension::Dynamic(None), Dimension::Dynamic(None)]
+    );
+
+    check_static_local(&output);
+    check_file_table(&output);
+
+    // The goto target of `count` is at an address inside of it.
+    let count = &output.find_subprograms("count")[0];
+    let (low_pc, high_pc) = count.ranges[0];
+    assert!(matches!(
+        count.labels[..],
+        [(ref name, address)] if name == "done" && (low_pc..high_pc).contains(&address)
+    ));
+
+    // Only the `static` function is local to the unit.
+    let external = |name| output.find_subprograms(name)[0].external;
+    assert!(external("sum") && external("main"));
+    assert!(!external("twice"));
+}
+
+// C functions have no linkage name, and are still kept as distinct subprograms rather than
+// replacing each other.
+#[test]
+fn test_c_unlinked_functions() {
+    let output = parse_fixture("arrays-functions", &[]);
+    let unit = &output.units[0];
+    let names: Vec<_> = unit.subprograms.keys().map(String::as_str).collect();
+    assert_eq!(names, ["count", "main", "sum", "twice"]);
+    for (name, subprogram) in &unit.subprograms {
+        assert_eq!(&subprogram.name, name);
+        assert!(
+            subprogram.linkage_name.is_empty(),
+            "{} has a linkage name",
+            name
+        );
+    }
+    let (sum, count) = (&unit.subprograms["sum"], &unit.subprograms["count"]);
+    assert_ne!(sum.die_offset, count.die_offset);
+    assert_ne!(sum.ranges, count.ranges);
+}
+
+// With optimizations, GCC moves the bounds into artificial variables that the subranges refer to.
+#[test]
+fn test_c_arrays_optimized() {
+    let output = parse_fixture("arrays-optimized", &["-O2"]);
+    let references = arrays(&output)
+        .flat_map(|node| &node.dimensions)
+        .filter(|dimension| matches!(dimension, Dimension::Dynamic(Some(_))))
+        .count();
+    assert!(references > 0, "no bound refers to a DIE");
+    assert_eq!(dynamic_dimensions(&output).len(), 2);
+
+    // `count` is also inlined into `main`, and its static local is only described in the abstract instance.
+    check_static_local(&output);
+}
+
+// Before DWARF5, file indices are 1-based, and file 0 of the table is the primary source file.
+#[test]
+fn test_c_file_table_dwarf4() {
+    let output = parse_fixture("arrays-dwarf4", &["-O0", "-gdwarf-4"]);
+    assert_eq!(output.units[0].version, 4);
+    assert!(output.units[0].files[0].ends_with("test/arrays.c"));
+    check_file_table(&output);
+}
+
+// In an object file that is not linked, the addresses of DW_OP_addr are only filled in by relocations
+// against the symbols, and `.eh_frame` has PC-relative relocations that cannot be applied.
+#[test]
+fn test_c_object_file() {
+    let output = parse_fixture("arrays.o", &["-O0", "-c"]);
+    let binary = fs::read(PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("arrays.o")).unwrap();
+    let object = object::File::parse(&*binary).unwrap();
+    let symbol = |name| {
+        object
+            .symbols()
+            .find(|symbol| symbol.name() == Ok(name))
+            .map(|symbol| symbol.address())
+    };
+
+    let last_message = output.units[0]
+        .globals
+        .iter()
+        .find(|global| global.name == "last_message")
+        .expect("last_message is not found");
+    let address = symbol("last_message").unwrap();
+    assert_ne!(address, 0);
+    assert!(matches!(
+        last_message.location[0].location,
+        Some(Location::Address(relocated)) if relocated == address
+    ));
+    check_static_local(&output);
+}
+
+// Without DW_AT_main_subprogram, the entry point is the subprogram at the entry address, which is
+// usually `_start` without debugging information, so the fixture is linked to start at `main`.
+#[test]
+fn test_c_entry_point() {
+    let output = parse_fixture("arrays-entry", &["-O0", "-Wl,-e,main"]);
+    let main = output
+        .main_subprogram()
+        .expect("the entry point is not found");
+    assert_eq!(main.name, "main");
+    assert!(parse_fixture("arrays-start", &["-O0"])
+        .main_subprogram()
+        .is_none());
+}
+
+// The `next` pointer of a list node refers back to the node, which is named rather than expanded
+// again, both in the type names and in the inlined type tree.
+#[test]
+fn test_c_self_referential() {
+    let options = ParseOptions {
+        inline_types: true,
+        ..Default::default()
+    };
+    let data = compile("list.c", "list", &[]);
+    let output = gimli_parser::parse_dwarf_with_options(&data, &options).unwrap();
+    let unit = &output.units[0];
+    let head = unit
+        .globals
+        .iter()
+        .find(|variable| variable.name == "head")
+        .expect("head is not found");
+    assert_eq!(head.type_name.as_deref(), Some("struct Node"));
+
+    // The pointee of `next` is the node itself.
+    let node = &unit.types[&head.var_type];
+    let next = &node.members[0];
+    assert_eq!(next.name, "next");
+    let pointer = &unit.types[&next.member_type];
+    assert_eq!(pointer.kind, TypeKind::Pointer);
+    assert_eq!(pointer.inner_type, head.var_type);
+
+    let tree = head.type_tree.as_ref().unwrap();
+    assert_eq!(tree.name.as_deref(), Some("struct Node"));
+    assert!(!tree.truncated);
+    let next = &tree.members[0].member_type;
+    assert_eq!(next.name.as_deref(), Some("struct Node *"));
+    let pointee = next.inner.as_ref().unwrap();
+    assert_eq!(pointee.name.as_deref(), Some("struct Node"));
+    assert!(pointee.truncated);
+    assert!(pointee.members.is_empty());
+}
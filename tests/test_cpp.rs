@@ -0,0 +1,200 @@
+// Compile `test/classes.cpp` with debug info, parse it, and check the C++ specifics of its types and
+// functions.
+//
+// The fixture is compiled with `c++` from the PATH, or with `$CXX` when set.
+
+use gimli_parser::{Demangle, Mangling, ParseOptions, ParseOutput, Type, TypeKind};
+use std::path::PathBuf;
+use std::process::Command;
+use std::{env, fs};
+
+// Compile the fixture into the temporary directory of the tests.
+fn parse_fixture(name: &str, flags: &[&str]) -> ParseOutput {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/classes.cpp");
+    let binary = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let cxx = env::var("CXX").unwrap_or_else(|_| "c++".to_string());
+    let status = Command::new(cxx)
+        .args(["-g", "-O0"])
+        .args(flags)
+        .arg(&fixture)
+        .arg("-o")
+        .arg(&binary)
+        .status()
+        .expect("failed to run c++");
+    assert!(status.success(), "failed to compile {}", fixture.display());
+    gimli_parser::parse_dwarf(&fs::read(binary).unwrap()).unwrap()
+}
+
+fn find_type<'a>(output: &'a ParseOutput, kind: TypeKind, name: &str) -> &'a Type {
+    output
+        .units
+        .iter()
+        .flat_map(|unit| unit.types.values())
+        .find(|node| node.kind == kind && node.name == name)
+        .unwrap_or_else(|| panic!("{:?} {} is not found", kind, name))
+}
+
+fn accessibility(node: &Type) -> Vec<(&str, Option<&str>)> {
+    node.members
+        .iter()
+        .map(|member| (member.name.as_str(), member.accessibility.as_deref()))
+        .collect()
+}
+
+// Compilers omit DW_AT_accessibility when it is the default of the class key, so both the
+// attribute and the default are checked.
+fn check_accessibility(output: &ParseOutput) {
+    let account = find_type(output, TypeKind::Class, "Account");
+    assert_eq!(
+        accessibility(account),
+        [
+            ("id", Some("private")),
+            ("balance", Some("public")),
+            ("kind", Some("protected")),
+        ]
+    );
+    let point = find_type(output, TypeKind::Struct, "Point");
+    assert_eq!(
+        accessibility(point),
+        [("x", Some("public")), ("y", Some("private"))]
+    );
+    let value = find_type(output, TypeKind::Union, "Value");
+    assert_eq!(
+        accessibility(value),
+        [("integer", Some("public")), ("real", Some("public"))]
+    );
+}
+
+#[test]
+fn test_cpp_accessibility() {
+    check_accessibility(&parse_fixture("classes", &[]));
+}
+
+// With -fdebug-types-section, the classes are defined in type units, and the globals refer to them
+// either directly by signature, or through a declaration in the unit that has the signature.
+#[test]
+fn test_cpp_type_units() {
+    for version in ["-gdwarf-4", "-gdwarf-5"] {
+        let output = parse_fixture(
+            &format!("classes-type-units{}", version),
+            &[version, "-fdebug-types-section"],
+        );
+        check_accessibility(&output);
+
+        let global_type = |name: &str| {
+            let global = output
+                .units
+                .iter()
+                .flat_map(|unit| &unit.globals)
+                .find(|variable| variable.name == name)
+                .unwrap_or_else(|| panic!("{} is not found", name));
+            output
+                .units
+                .iter()
+                .find_map(|unit| unit.types.get(&global.var_type))
+                .unwrap_or_else(|| panic!("the type of {} is not found", name))
+        };
+        let point = global_type("point");
+        assert_eq!(point.name, "Point");
+        assert_eq!(point.byte_size, Some(8));
+        assert_eq!(accessibility(point).len(), 2);
+        let shape = global_type("shape");
+        assert_eq!(shape.name, "geometry::Shape");
+        assert_eq!(shape.byte_size, Some(1));
+    }
+}
+
+#[test]
+fn test_cpp_void() {
+    let output = parse_fixture("classes-void", &[]);
+
+    // A function without a return type returns void.
+    let reset = output.find_subprograms("reset");
+    assert_eq!(reset.len(), 1);
+    assert_eq!(reset[0].ret_type, 0);
+    assert_eq!(reset[0].ret_type_name.as_deref(), Some("void"));
+    let main = output.find_subprograms("main");
+    assert_eq!(main[0].ret_type_name.as_deref(), Some("int"));
+
+    let global = |name: &str| {
+        output
+            .units
+            .iter()
+            .flat_map(|unit| &unit.globals)
+            .find(|variable| variable.name == name)
+            .unwrap_or_else(|| panic!("{} is not found", name))
+    };
+    assert_eq!(global("opaque").type_name.as_deref(), Some("void *"));
+
+    // `decltype(nullptr)` is a DW_TAG_unspecified_type.
+    let null_pointer = global("null_pointer");
+    assert_eq!(null_pointer.type_name.as_deref(), Some("decltype(nullptr)"));
+    let types: Vec<_> = output
+        .units
+        .iter()
+        .flat_map(|unit| unit.types.get(&null_pointer.var_type))
+        .collect();
+    assert_eq!(types.len(), 1);
+    assert_eq!(types[0].kind, TypeKind::Unspecified);
+}
+
+#[test]
+fn test_cpp_containing_type() {
+    let output = parse_fixture("classes-methods", &[]);
+    let containing_type = |name: &str| {
+        let subprograms = output.find_subprograms(name);
+        assert_eq!(subprograms.len(), 1, "{} is not found", name);
+        subprograms[0].containing_type.clone()
+    };
+
+    // Both the definition outside of the class and the one inside it belong to the class.
+    let shape = Some("geometry::Shape".to_string());
+    assert_eq!(containing_type("geometry::Shape::area"), shape);
+    assert_eq!(containing_type("geometry::Shape::sides"), shape);
+    assert_eq!(containing_type("reset"), None);
+}
+
+// The `_Z` names of a C++ unit are demangled as C++, and are left mangled rather than misread when
+// they are forced to the scheme of rustc.
+#[test]
+fn test_cpp_demangle() {
+    let output = parse_fixture("classes-demangle", &[]);
+    let area = &output.find_subprograms("geometry::Shape::area")[0];
+    assert_eq!(area.linkage_name, "_ZNK8geometry5Shape4areaEv");
+    assert_eq!(
+        area.demangled_name.as_deref(),
+        Some("geometry::Shape::area() const")
+    );
+    assert_eq!(area.mangling, Some(Mangling::Cpp));
+
+    let binary = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("classes-demangle");
+    let data = fs::read(binary).unwrap();
+    for demangle in [Demangle::Rust, Demangle::None] {
+        let options = ParseOptions {
+            demangle,
+            ..Default::default()
+        };
+        let output = gimli_parser::parse_dwarf_with_options(&data, &options).unwrap();
+        let area = &output.find_subprograms("geometry::Shape::area")[0];
+        assert_eq!(
+            (area.demangled_name.as_deref(), area.mangling),
+            (None, None)
+        );
+    }
+}
+
+// The `this` of a method is its first parameter, and is generated by the compiler.
+#[test]
+fn test_cpp_this() {
+    let output = parse_fixture("classes-this", &[]);
+    let first_parameter = |name: &str| {
+        let subprogram = &output.find_subprograms(name)[0];
+        let variable = &subprogram.variables[0];
+        assert!(variable.parameter, "{} has no parameter", name);
+        (variable.name.as_str(), variable.artificial)
+    };
+
+    assert_eq!(first_parameter("geometry::Shape::area"), ("this", true));
+    assert_eq!(first_parameter("geometry::Shape::sides"), ("this", true));
+    assert_eq!(first_parameter("reset"), ("target", false));
+}
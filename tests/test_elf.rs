@@ -0,0 +1,210 @@
+// Compile `test/test.rs` with debug info, parse it, and check the subprograms and variables
+// that the handlers extract from it.
+//
+// The fixture is compiled with `rustc` from the PATH, or with `$RUSTC` when set, and the debuglink
+// test needs `objcopy` and `strip` from binutils.
+
+use gimli_parser::{
+    Location, Mangling, ParseError, ParseOptions, ParseOutput, Subprogram, Variable,
+};
+use object::{CompressionFormat, Object, ObjectSection};
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+use std::process::Command;
+use std::{env, fs};
+
+// Compile the fixture into the temporary directory of the tests.
+fn compile_fixture(name: &str, flags: &[&str]) -> PathBuf {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/test.rs");
+    let binary = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let status = Command::new(rustc)
+        .args(["-g", "-Copt-level=0", "-Adead_code"])
+        .args(flags)
+        .arg(&fixture)
+        .arg("-o")
+        .arg(&binary)
+        .status()
+        .expect("failed to run rustc");
+    assert!(status.success(), "failed to compile {}", fixture.display());
+    binary
+}
+
+fn parse_fixture(name: &str, flags: &[&str]) -> ParseOutput {
+    let data = fs::read(compile_fixture(name, flags)).unwrap();
+    gimli_parser::parse_dwarf(&data).unwrap()
+}
+
+// Get the stack offset of a variable, which must have a single location over its whole scope.
+fn stack_offset(variable: &Variable) -> i64 {
+    match variable.location.as_slice() {
+        [entry] => match entry.location {
+            Some(Location::Stack(offset)) => offset,
+            ref location => panic!("{} is not on the stack: {:?}", variable.name, location),
+        },
+        entries => panic!("{} has {} locations", variable.name, entries.len()),
+    }
+}
+
+fn variables<'a>(main: &'a Subprogram, name: &str) -> Vec<&'a Variable> {
+    main.variables
+        .iter()
+        .filter(|variable| variable.name == name)
+        .collect()
+}
+
+fn check_main(output: &ParseOutput) {
+    let main = output.main_subprogram().expect("main is not found");
+    assert_eq!(main.name, "test::main");
+    assert!(main.linkage_name.starts_with("_ZN4test4main"));
+    assert_eq!(main.demangled_name.as_deref(), Some("test::main"));
+    assert_eq!(main.mangling, Some(Mangling::Rust));
+    assert!(!main.declaration);
+    assert!(!main.ranges.is_empty());
+
+    let father = variables(main, "father");
+    assert_eq!(father.len(), 1);
+    let father = father[0];
+    assert_eq!(
+        father.type_name.as_deref(),
+        Some("struct test::MyFatherStruct")
+    );
+    assert_eq!(output.type_size(father.var_type), Some(8));
+
+    // The shadowed binding and the one in the nested block are both kept, in declaration order.
+    let locals = variables(main, "main_local_variable");
+    assert_eq!(locals.len(), 2);
+    for local in &locals {
+        assert_eq!(local.type_name.as_deref(), Some("i32"));
+        assert_eq!(output.type_size(local.var_type), Some(4));
+    }
+
+    // The binding in the nested block is live only within the range of the outer one, which is
+    // within main.
+    let (begin, end) = locals[0].live_range.unwrap();
+    let (inner_begin, inner_end) = locals[1].live_range.unwrap();
+    assert!(begin < inner_begin && inner_end < end);
+    assert!(main
+        .ranges
+        .iter()
+        .any(|&(low, high)| low <= begin && end <= high));
+
+    // The three variables live at distinct, non-overlapping stack slots.
+    let mut slots = [
+        (stack_offset(father), 8),
+        (stack_offset(locals[0]), 4),
+        (stack_offset(locals[1]), 4),
+    ];
+    slots.sort();
+    for pair in slots.windows(2) {
+        assert!(
+            pair[0].0 + pair[0].1 <= pair[1].0,
+            "overlapping stack slots: {:?}",
+            slots
+        );
+    }
+    for (offset, size) in slots {
+        assert_eq!(offset % size, 0, "misaligned stack slot at {}", offset);
+    }
+}
+
+#[test]
+fn test_elf() {
+    let output = parse_fixture("test.elf", &[]);
+    check_main(&output);
+}
+
+#[test]
+fn test_elf_dwarf5() {
+    let output = parse_fixture("test-dwarf5.elf", &["-Cdwarf-version=5"]);
+    // The standard library is prebuilt, so only the unit of the fixture is DWARF5.
+    let unit = output
+        .units
+        .iter()
+        .find(|unit| {
+            unit.subprograms
+                .values()
+                .any(|subprogram| subprogram.is_main)
+        })
+        .unwrap();
+    assert_eq!(unit.version, 5);
+    check_main(&output);
+}
+
+// Units are parsed in parallel, and the output must still not depend on the order they finish in.
+#[test]
+fn test_elf_deterministic() {
+    let data = fs::read(compile_fixture("test-deterministic.elf", &[])).unwrap();
+    let serialize = || {
+        let output = gimli_parser::parse_dwarf(&data).unwrap();
+        serde_json::to_vec(&(output.units, output.lines)).unwrap()
+    };
+    let first = serialize();
+    assert!(first == serialize(), "the two outputs differ");
+}
+
+#[test]
+fn test_elf_zstd() {
+    let binary = compile_fixture(
+        "test-zstd.elf",
+        &["-Clink-arg=-Wl,--compress-debug-sections=zstd"],
+    );
+    let data = fs::read(binary).unwrap();
+
+    // Make sure the linker did compress the sections, or the test would pass trivially.
+    let object = object::File::parse(&*data).unwrap();
+    let debug_info = object.section_by_name(".debug_info").unwrap();
+    let compression = debug_info.compressed_file_range().unwrap();
+    assert_eq!(compression.format, CompressionFormat::Zstandard);
+
+    let output = gimli_parser::parse_dwarf(&data).unwrap();
+    check_main(&output);
+}
+
+// Run a binutils tool from the PATH.
+fn binutils(tool: &str, args: &[&OsStr]) {
+    let status = Command::new(tool)
+        .args(args)
+        .status()
+        .unwrap_or_else(|_| panic!("failed to run {}", tool));
+    assert!(status.success(), "{} failed with {:?}", tool, args);
+}
+
+#[test]
+fn test_elf_debuglink() {
+    // Move the DWARF of the fixture to a separate debug file, as for a stripped release binary.
+    let binary = compile_fixture("test-debuglink.elf", &[]);
+    let debug_file = binary.with_extension("debug");
+    let mut link_arg = OsString::from("--add-gnu-debuglink=");
+    link_arg.push(&debug_file);
+    binutils(
+        "objcopy",
+        &[
+            "--only-keep-debug".as_ref(),
+            binary.as_ref(),
+            debug_file.as_ref(),
+        ],
+    );
+    binutils("strip", &["--strip-debug".as_ref(), binary.as_ref()]);
+    binutils("objcopy", &[&link_arg, binary.as_ref()]);
+
+    let data = fs::read(&binary).unwrap();
+    let debug_data = fs::read(&debug_file).unwrap();
+
+    // The stripped binary has no DWARF of its own, and names its debug file.
+    assert!(matches!(
+        gimli_parser::parse_dwarf(&data),
+        Err(ParseError::NoDebugInfo)
+    ));
+    let info = gimli_parser::file_info(&data).unwrap();
+    assert!(!info.debug_info);
+    assert_eq!(info.debug_link.as_deref(), Some("test-debuglink.debug"));
+    let link = gimli_parser::debug_link(&data).unwrap().unwrap();
+    assert_eq!(link.file_name, "test-debuglink.debug");
+    assert!(link.matches(&debug_data));
+    assert!(!link.matches(&data));
+
+    let options = ParseOptions::default();
+    let output = gimli_parser::parse_dwarf_with_debug_file(&data, &debug_data, &options).unwrap();
+    check_main(&output);
+}
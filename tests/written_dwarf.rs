@@ -0,0 +1,790 @@
+// Write DWARF with `gimli::write` into an ELF relocatable object, parse it, and check the attributes
+// that no compiler on the test machine emits, e.g. vendor calling conventions.
+// The forms that `gimli::write` cannot emit, e.g. DW_FORM_implicit_const, are assembled by hand.
+
+use gimli::leb128;
+use gimli::write::{
+    Address, AttributeValue, Dwarf, DwarfUnit, EndianVec, Expression, LineProgram, LineString,
+    Reference, Sections, Unit, UnitEntryId,
+};
+use gimli_parser::{Location, ParseOutput, TypeKind};
+use object::write::{Object, Symbol, SymbolSection};
+use object::{
+    Architecture, BinaryFormat, Endianness, SectionKind, SymbolFlags, SymbolKind, SymbolScope,
+};
+
+const ENCODING: gimli::Encoding = encoding(gimli::Format::Dwarf32);
+
+// The encoding of DWARF5 for a 64-bit target, in the 32-bit or 64-bit DWARF format.
+const fn encoding(format: gimli::Format) -> gimli::Encoding {
+    gimli::Encoding {
+        format,
+        version: 5,
+        address_size: 8,
+    }
+}
+
+// Write a DWARF5 unit whose DIEs are added by `build` below the root, and parse it.
+fn parse_unit(build: impl FnOnce(&mut DwarfUnit, UnitEntryId)) -> ParseOutput {
+    let mut dwarf = DwarfUnit::new(ENCODING);
+    let root = dwarf.unit.root();
+    dwarf.unit.get_mut(root).set(
+        gimli::DW_AT_name,
+        AttributeValue::String(b"written.c".to_vec()),
+    );
+    build(&mut dwarf, root);
+    parse_sections(write_sections(&mut dwarf))
+}
+
+// Write the non-empty sections of a unit, by name.
+fn write_sections(dwarf: &mut DwarfUnit) -> Vec<(&'static str, Vec<u8>)> {
+    let mut sections = Sections::new(EndianVec::new(gimli::LittleEndian));
+    dwarf.write(&mut sections).unwrap();
+    section_data(sections)
+}
+
+// Write the non-empty sections of several units, by name.
+fn write_dwarf_sections(dwarf: &mut Dwarf) -> Vec<(&'static str, Vec<u8>)> {
+    let mut sections = Sections::new(EndianVec::new(gimli::LittleEndian));
+    dwarf.write(&mut sections).unwrap();
+    section_data(sections)
+}
+
+// Take the non-empty sections, by name.
+fn section_data(
+    sections: Sections<EndianVec<gimli::LittleEndian>>,
+) -> Vec<(&'static str, Vec<u8>)> {
+    let mut written = Vec::new();
+    sections
+        .for_each(|id, data| {
+            if !data.slice().is_empty() {
+                written.push((id.name(), data.slice().to_vec()));
+            }
+            Ok::<_, gimli::write::Error>(())
+        })
+        .unwrap();
+    written
+}
+
+// Put the sections into an ELF relocatable object, and parse it.
+fn parse_sections(sections: Vec<(&'static str, Vec<u8>)>) -> ParseOutput {
+    parse_object(sections, &[])
+}
+
+// Put the sections into an ELF relocatable object with a `.text` section that has the function symbols
+// of `symbols`, given by name, address, and size, and parse it.
+fn parse_object(
+    sections: Vec<(&'static str, Vec<u8>)>,
+    symbols: &[(&str, u64, u64)],
+) -> ParseOutput {
+    let mut object = Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+    for (name, data) in sections {
+        let section = object.add_section(Vec::new(), name.as_bytes().to_vec(), SectionKind::Debug);
+        object.set_section_data(section, data, 1);
+    }
+    if !symbols.is_empty() {
+        let text = object.add_section(Vec::new(), b".text".to_vec(), SectionKind::Text);
+        object.set_section_data(text, vec![0xc3; 0x100], 16);
+        for &(name, value, size) in symbols {
+            object.add_symbol(Symbol {
+                name: name.as_bytes().to_vec(),
+                value,
+                size,
+                kind: SymbolKind::Text,
+                scope: SymbolScope::Linkage,
+                weak: false,
+                section: SymbolSection::Section(text),
+                flags: SymbolFlags::None,
+            });
+        }
+    }
+    gimli_parser::parse_dwarf(&object.write().unwrap()).unwrap()
+}
+
+// Append an abbreviation to `.debug_abbrev`, where the attributes with a value are DW_FORM_implicit_const.
+fn add_abbrev(
+    abbrevs: &mut Vec<u8>,
+    code: u64,
+    tag: gimli::DwTag,
+    children: bool,
+    attrs: &[(gimli::DwAt, gimli::DwForm, Option<i64>)],
+) {
+    leb128::write::unsigned(abbrevs, code).unwrap();
+    leb128::write::unsigned(abbrevs, tag.0.into()).unwrap();
+    abbrevs.push(children.into());
+    for &(name, form, value) in attrs {
+        leb128::write::unsigned(abbrevs, name.0.into()).unwrap();
+        leb128::write::unsigned(abbrevs, form.0.into()).unwrap();
+        if let Some(value) = value {
+            leb128::write::signed(abbrevs, value).unwrap();
+        }
+    }
+    abbrevs.extend([0, 0]);
+}
+
+// Append a DIE to `.debug_info` that starts with its abbreviation code and has inline string names,
+// and return its offset in the unit.
+fn add_die(info: &mut Vec<u8>, code: u64, names: &[&str]) -> u32 {
+    let offset = info.len() as u32;
+    leb128::write::unsigned(info, code).unwrap();
+    for name in names {
+        info.extend(name.as_bytes());
+        info.push(0);
+    }
+    offset
+}
+
+// Add a subprogram with a name and the attributes of `attrs` below `parent`.
+fn add_subprogram(
+    dwarf: &mut DwarfUnit,
+    parent: UnitEntryId,
+    name: &str,
+    attrs: Vec<(gimli::DwAt, AttributeValue)>,
+) -> UnitEntryId {
+    let subprogram = dwarf.unit.add(parent, gimli::DW_TAG_subprogram);
+    let entry = dwarf.unit.get_mut(subprogram);
+    entry.set(
+        gimli::DW_AT_name,
+        AttributeValue::String(name.as_bytes().to_vec()),
+    );
+    for (name, value) in attrs {
+        entry.set(name, value);
+    }
+    subprogram
+}
+
+#[test]
+fn test_calling_convention() {
+    let conventions = [
+        ("normal", None, None),
+        ("nocall", Some(gimli::DW_CC_nocall), Some("DW_CC_nocall")),
+        ("win64", Some(gimli::DwCc(0xc1)), Some("DW_CC_LLVM_Win64")),
+        (
+            "renesas",
+            Some(gimli::DwCc(0x40)),
+            Some("DW_CC_GNU_renesas_sh"),
+        ),
+        ("vendor", Some(gimli::DwCc(0x90)), Some("DW_CC_0x90")),
+    ];
+    let output = parse_unit(|dwarf, root| {
+        for (name, convention, _) in conventions {
+            let attrs = convention
+                .map(|convention| {
+                    (
+                        gimli::DW_AT_calling_convention,
+                        AttributeValue::CallingConvention(convention),
+                    )
+                })
+                .into_iter()
+                .collect();
+            add_subprogram(dwarf, root, name, attrs);
+        }
+    });
+    for (name, _, expected) in conventions {
+        let subprogram = output.find_subprograms(name);
+        assert_eq!(subprogram.len(), 1, "{} is not found", name);
+        assert_eq!(subprogram[0].calling_convention.as_deref(), expected);
+    }
+}
+
+// The entry point of the program is only marked on the root DIE of its unit, e.g. by compilers that
+// emit DW_AT_main_subprogram for the unit but not for the subprogram.
+#[test]
+fn test_main_unit() {
+    for flag in [false, true] {
+        let output = parse_unit(|dwarf, root| {
+            dwarf
+                .unit
+                .get_mut(root)
+                .set(gimli::DW_AT_main_subprogram, AttributeValue::Flag(flag));
+            add_subprogram(dwarf, root, "helper", Vec::new());
+            add_subprogram(dwarf, root, "main", Vec::new());
+        });
+        let main = output
+            .main_subprogram()
+            .map(|subprogram| subprogram.name.as_str());
+        assert_eq!(main, flag.then_some("main"));
+    }
+}
+
+// GCC puts constants that are the same in every DIE of an abbreviation, e.g. DW_AT_decl_file, into the
+// abbreviation itself as DW_FORM_implicit_const, which the DIEs then have no bytes for.
+#[test]
+fn test_implicit_const() {
+    use gimli::{constants::*, DwForm};
+    const IMPLICIT: DwForm = DW_FORM_implicit_const;
+
+    // The line program is written by gimli for the file table of DW_AT_decl_file, where file 0 is
+    // the primary source file and file 1 is the header.
+    let mut dwarf = DwarfUnit::new(ENCODING);
+    let mut program = LineProgram::new(
+        ENCODING,
+        Default::default(),
+        LineString::String(b"/src".to_vec()),
+        LineString::String(b"fixture.c".to_vec()),
+        None,
+    );
+    let directory = program.default_directory();
+    let header = program.add_file(LineString::String(b"fixture.h".to_vec()), directory, None);
+    dwarf.unit.line_program = program;
+    // The line program is only written if a DIE refers to one of its files.
+    let root = dwarf.unit.root();
+    dwarf.unit.get_mut(root).set(
+        gimli::DW_AT_decl_file,
+        AttributeValue::FileIndex(Some(header)),
+    );
+    let mut sections: Vec<_> = write_sections(&mut dwarf)
+        .into_iter()
+        .filter(|(name, _)| *name == ".debug_line")
+        .collect();
+
+    let mut abbrevs = Vec::new();
+    let name = (DW_AT_name, DW_FORM_string, None);
+    let decl_file = (DW_AT_decl_file, IMPLICIT, Some(1));
+    let int_type = (DW_AT_type, DW_FORM_ref4, None);
+    add_abbrev(
+        &mut abbrevs,
+        1,
+        DW_TAG_compile_unit,
+        true,
+        &[
+            name,
+            (DW_AT_comp_dir, DW_FORM_string, None),
+            (DW_AT_stmt_list, DW_FORM_sec_offset, None),
+            (DW_AT_language, IMPLICIT, Some(DW_LANG_C11.0.into())),
+        ],
+    );
+    add_abbrev(
+        &mut abbrevs,
+        2,
+        DW_TAG_base_type,
+        false,
+        &[
+            name,
+            (DW_AT_byte_size, IMPLICIT, Some(4)),
+            (DW_AT_encoding, IMPLICIT, Some(DW_ATE_signed.0.into())),
+        ],
+    );
+    add_abbrev(
+        &mut abbrevs,
+        3,
+        DW_TAG_structure_type,
+        true,
+        &[
+            (DW_AT_byte_size, IMPLICIT, Some(8)),
+            decl_file,
+            (DW_AT_decl_line, IMPLICIT, Some(3)),
+        ],
+    );
+    add_abbrev(
+        &mut abbrevs,
+        4,
+        DW_TAG_member,
+        false,
+        &[
+            name,
+            int_type,
+            (DW_AT_data_member_location, IMPLICIT, Some(4)),
+        ],
+    );
+    add_abbrev(
+        &mut abbrevs,
+        5,
+        DW_TAG_enumeration_type,
+        true,
+        &[name, int_type, (DW_AT_byte_size, IMPLICIT, Some(4))],
+    );
+    add_abbrev(
+        &mut abbrevs,
+        6,
+        DW_TAG_enumerator,
+        false,
+        &[name, (DW_AT_const_value, IMPLICIT, Some(-1))],
+    );
+    add_abbrev(
+        &mut abbrevs,
+        7,
+        DW_TAG_subprogram,
+        false,
+        &[
+            name,
+            decl_file,
+            (DW_AT_external, DW_FORM_flag_present, None),
+        ],
+    );
+    add_abbrev(&mut abbrevs, 8, DW_TAG_variable, false, &[name, int_type]);
+    abbrevs.push(0);
+
+    // The unit header is the unit length, the version, the unit type, the address size, and the
+    // offset of the abbreviations.
+    let mut info = vec![0; 4];
+    info.extend(5u16.to_le_bytes());
+    info.extend([DW_UT_compile.0, 8]);
+    info.extend(0u32.to_le_bytes());
+    add_die(&mut info, 1, &["fixture.c", "/src"]);
+    info.extend(0u32.to_le_bytes());
+    let int = add_die(&mut info, 2, &["int"]);
+    add_die(&mut info, 3, &[]);
+    add_die(&mut info, 4, &["second"]);
+    info.extend(int.to_le_bytes());
+    info.push(0);
+    add_die(&mut info, 5, &["status"]);
+    info.extend(int.to_le_bytes());
+    add_die(&mut info, 6, &["failed"]);
+    info.push(0);
+    let declared = add_die(&mut info, 7, &["declared"]);
+    let counter = add_die(&mut info, 8, &["counter"]);
+    info.extend(int.to_le_bytes());
+    info.push(0);
+    let length = info.len() as u32 - 4;
+    info[..4].copy_from_slice(&length.to_le_bytes());
+    sections.push((".debug_abbrev", abbrevs));
+    sections.push((".debug_info", info));
+
+    let output = parse_sections(sections);
+    let unit = &output.units[0];
+    assert_eq!(unit.language.as_deref(), Some("DW_LANG_C11"));
+    let find_type = |kind| {
+        unit.types
+            .values()
+            .find(|node| node.kind == kind)
+            .unwrap_or_else(|| panic!("{:?} is not found", kind))
+    };
+
+    let int = find_type(TypeKind::Base);
+    assert_eq!(int.byte_size, Some(4));
+    assert_eq!(int.encoding.as_deref(), Some("DW_ATE_signed"));
+
+    let pair = find_type(TypeKind::Struct);
+    assert_eq!(pair.name, "(anonymous struct at /src/fixture.h:3)");
+    assert_eq!(pair.byte_size, Some(8));
+    assert_eq!(pair.members[0].offset, Some(4));
+
+    // DW_FORM_implicit_const is a signed LEB128.
+    let status = find_type(TypeKind::Enum);
+    assert_eq!(status.enumerators[0].value, -1);
+
+    let subprogram = output.find_subprograms("declared");
+    assert_eq!(subprogram[0].decl_file.as_deref(), Some("/src/fixture.h"));
+    assert_eq!(subprogram[0].decl_file_index, Some(1));
+    assert_eq!(unit.files, ["/src/fixture.c", "/src/fixture.h"]);
+    assert!(subprogram[0].external);
+
+    // The offsets refer back to the unit and its DIEs in `.debug_info`.
+    assert_eq!(unit.offset, 0);
+    assert_eq!(subprogram[0].die_offset, declared as usize);
+    assert_eq!(unit.globals[0].name, "counter");
+    assert_eq!(unit.globals[0].die_offset, counter as usize);
+}
+
+// The qualifiers of a pointer type are written after the `*` they apply to, as in C declarations.
+#[test]
+fn test_qualified_pointer_names() {
+    let output = parse_unit(|dwarf, root| {
+        let mut add_type = |tag, inner: Option<UnitEntryId>| {
+            let node = dwarf.unit.add(root, tag);
+            let entry = dwarf.unit.get_mut(node);
+            match inner {
+                Some(inner) => entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(inner)),
+                None => {
+                    entry.set(gimli::DW_AT_name, AttributeValue::String(b"char".to_vec()));
+                    entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(1));
+                }
+            }
+            node
+        };
+        let char_type = add_type(gimli::DW_TAG_base_type, None);
+        let const_char = add_type(gimli::DW_TAG_const_type, Some(char_type));
+        let pointer_to_const = add_type(gimli::DW_TAG_pointer_type, Some(const_char));
+        let pointer = add_type(gimli::DW_TAG_pointer_type, Some(char_type));
+        let const_pointer = add_type(gimli::DW_TAG_const_type, Some(pointer));
+        let const_pointer_to_const = add_type(gimli::DW_TAG_const_type, Some(pointer_to_const));
+        let pointer_to_pointers =
+            add_type(gimli::DW_TAG_pointer_type, Some(const_pointer_to_const));
+
+        for (name, node) in [
+            ("message", pointer_to_const),
+            ("buffer", const_pointer),
+            ("argv", pointer_to_pointers),
+        ] {
+            let variable = dwarf.unit.add(root, gimli::DW_TAG_variable);
+            let entry = dwarf.unit.get_mut(variable);
+            entry.set(
+                gimli::DW_AT_name,
+                AttributeValue::String(name.as_bytes().to_vec()),
+            );
+            entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(node));
+        }
+    });
+    let names: Vec<_> = output.units[0]
+        .globals
+        .iter()
+        .map(|global| (global.name.as_str(), global.type_name.as_deref()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("message", Some("const char *")),
+            ("buffer", Some("char * const")),
+            ("argv", Some("const char * const *")),
+        ]
+    );
+}
+
+// A location expression that cannot be decoded only loses the location of its variable, and keeps
+// its bytes, instead of failing the whole file.
+#[test]
+fn test_malformed_expression() {
+    let output = parse_unit(|dwarf, root| {
+        for (name, bytes) in [
+            ("broken", vec![0xff]),
+            ("fixed", vec![0x03, 0x10, 0, 0, 0, 0, 0, 0, 0]),
+        ] {
+            let variable = dwarf.unit.add(root, gimli::DW_TAG_variable);
+            let entry = dwarf.unit.get_mut(variable);
+            entry.set(
+                gimli::DW_AT_name,
+                AttributeValue::String(name.as_bytes().to_vec()),
+            );
+            entry.set(
+                gimli::DW_AT_location,
+                AttributeValue::Exprloc(Expression::raw(bytes)),
+            );
+        }
+    });
+    let globals = &output.units[0].globals;
+    assert_eq!(globals[0].name, "broken");
+    assert!(globals[0].location[0].location.is_none());
+    assert_eq!(globals[0].raw_location.as_deref(), Some("ff"));
+    assert!(matches!(
+        globals[1].location[0].location,
+        Some(Location::Address(0x10))
+    ));
+}
+
+// A malformed member offset only loses the offset of that member, as for the locations above.
+#[test]
+fn test_malformed_member_offset() {
+    let output = parse_unit(|dwarf, root| {
+        let node = dwarf.unit.add(root, gimli::DW_TAG_structure_type);
+        let entry = dwarf.unit.get_mut(node);
+        entry.set(gimli::DW_AT_name, AttributeValue::String(b"pair".to_vec()));
+        entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(16));
+        for (name, bytes) in [("broken", vec![0xff]), ("fixed", vec![0x23, 0x08])] {
+            let member = dwarf.unit.add(node, gimli::DW_TAG_member);
+            let entry = dwarf.unit.get_mut(member);
+            entry.set(
+                gimli::DW_AT_name,
+                AttributeValue::String(name.as_bytes().to_vec()),
+            );
+            entry.set(
+                gimli::DW_AT_data_member_location,
+                AttributeValue::Exprloc(Expression::raw(bytes)),
+            );
+        }
+    });
+    let pair = output.units[0]
+        .types
+        .values()
+        .find(|node| node.name == "pair")
+        .expect("pair is not found");
+    let offsets: Vec<_> = pair
+        .members
+        .iter()
+        .map(|member| (member.name.as_str(), member.offset))
+        .collect();
+    assert_eq!(offsets, [("broken", None), ("fixed", Some(8))]);
+}
+
+// The names of DWARF5 are often DW_FORM_strx, an index into the contribution of the unit to
+// `.debug_str_offsets`, which starts at its DW_AT_str_offsets_base.
+#[test]
+fn test_strx() {
+    use gimli::constants::*;
+
+    let mut strings = Vec::new();
+    let mut str_offsets = Vec::new();
+    for string in ["strx.c", "int", "counter"] {
+        str_offsets.extend((strings.len() as u32).to_le_bytes());
+        strings.extend(string.as_bytes());
+        strings.push(0);
+    }
+    // The header of the contribution is its length, the version, and padding.
+    let mut contribution = ((str_offsets.len() + 4) as u32).to_le_bytes().to_vec();
+    contribution.extend(5u16.to_le_bytes());
+    contribution.extend(0u16.to_le_bytes());
+    contribution.append(&mut str_offsets);
+
+    let mut abbrevs = Vec::new();
+    let name = (DW_AT_name, DW_FORM_strx1, None);
+    add_abbrev(
+        &mut abbrevs,
+        1,
+        DW_TAG_compile_unit,
+        true,
+        &[name, (DW_AT_str_offsets_base, DW_FORM_sec_offset, None)],
+    );
+    add_abbrev(
+        &mut abbrevs,
+        2,
+        DW_TAG_base_type,
+        false,
+        &[name, (DW_AT_byte_size, DW_FORM_data1, None)],
+    );
+    add_abbrev(
+        &mut abbrevs,
+        3,
+        DW_TAG_variable,
+        false,
+        &[name, (DW_AT_type, DW_FORM_ref4, None)],
+    );
+    abbrevs.push(0);
+
+    let mut info = vec![0; 4];
+    info.extend(5u16.to_le_bytes());
+    info.extend([DW_UT_compile.0, 8]);
+    info.extend(0u32.to_le_bytes());
+    add_die(&mut info, 1, &[]);
+    info.push(0);
+    info.extend(8u32.to_le_bytes());
+    let int = add_die(&mut info, 2, &[]);
+    info.extend([1, 4]);
+    add_die(&mut info, 3, &[]);
+    info.push(2);
+    info.extend(int.to_le_bytes());
+    info.push(0);
+    let length = info.len() as u32 - 4;
+    info[..4].copy_from_slice(&length.to_le_bytes());
+
+    let output = parse_sections(vec![
+        (".debug_abbrev", abbrevs),
+        (".debug_info", info),
+        (".debug_str", strings),
+        (".debug_str_offsets", contribution),
+    ]);
+    let unit = &output.units[0];
+    assert_eq!(unit.name.as_deref(), Some("strx.c"));
+    assert_eq!(unit.globals[0].name, "counter");
+    assert_eq!(unit.globals[0].type_name.as_deref(), Some("int"));
+}
+
+// The type references are offsets of the size of the DWARF format, which is 8 bytes in DWARF64 for the
+// references to another unit, DW_FORM_ref_addr, and for the offsets of the unit headers.
+#[test]
+fn test_type_references() {
+    for format in [gimli::Format::Dwarf32, gimli::Format::Dwarf64] {
+        let mut dwarf = Dwarf::new();
+        let mut add_unit = |name: &str| {
+            let mut unit = Unit::new(encoding(format), LineProgram::none());
+            let root = unit.root();
+            unit.get_mut(root).set(
+                gimli::DW_AT_name,
+                AttributeValue::String(name.as_bytes().to_vec()),
+            );
+            dwarf.units.add(unit)
+        };
+        let types = add_unit("types.c");
+        let user = add_unit("user.c");
+
+        // `struct point { int x; struct point *next; }` in the first unit.
+        let unit = dwarf.units.get_mut(types);
+        let root = unit.root();
+        let int = unit.add(root, gimli::DW_TAG_base_type);
+        let entry = unit.get_mut(int);
+        entry.set(gimli::DW_AT_name, AttributeValue::String(b"int".to_vec()));
+        entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(4));
+        entry.set(
+            gimli::DW_AT_encoding,
+            AttributeValue::Encoding(gimli::DW_ATE_signed),
+        );
+        let point = unit.add(root, gimli::DW_TAG_structure_type);
+        let entry = unit.get_mut(point);
+        entry.set(gimli::DW_AT_name, AttributeValue::String(b"point".to_vec()));
+        entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(16));
+        let pointer = unit.add(root, gimli::DW_TAG_pointer_type);
+        let entry = unit.get_mut(pointer);
+        entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(8));
+        entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(point));
+        for (name, member_type, offset) in [("x", int, 0), ("next", pointer, 8)] {
+            let member = unit.add(point, gimli::DW_TAG_member);
+            let entry = unit.get_mut(member);
+            entry.set(
+                gimli::DW_AT_name,
+                AttributeValue::String(name.as_bytes().to_vec()),
+            );
+            entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(member_type));
+            entry.set(
+                gimli::DW_AT_data_member_location,
+                AttributeValue::Udata(offset),
+            );
+        }
+
+        // `struct point *head;` in the second unit.
+        let unit = dwarf.units.get_mut(user);
+        let root = unit.root();
+        let head = unit.add(root, gimli::DW_TAG_variable);
+        let entry = unit.get_mut(head);
+        entry.set(gimli::DW_AT_name, AttributeValue::String(b"head".to_vec()));
+        entry.set(
+            gimli::DW_AT_type,
+            AttributeValue::DebugInfoRef(Reference::Entry(types, pointer)),
+        );
+
+        let output = parse_sections(write_dwarf_sections(&mut dwarf));
+        let head = &output.units[1].globals[0];
+        assert_eq!(
+            head.type_name.as_deref(),
+            Some("struct point *"),
+            "{:?}",
+            format
+        );
+        assert_eq!(output.type_size(head.var_type), Some(8));
+
+        let pointer = &output.units[0].types[&head.var_type];
+        let point = &output.units[0].types[&pointer.inner_type];
+        assert_eq!(point.name, "point");
+        let members: Vec<_> = point
+            .members
+            .iter()
+            .map(|member| (member.name.as_str(), output.type_size(member.member_type)))
+            .collect();
+        assert_eq!(members, [("x", Some(4)), ("next", Some(8))]);
+        assert_eq!(point.members[1].member_type, head.var_type);
+    }
+}
+
+// Add an array type of `element` with a subrange per count and stride, and the attributes of `attrs`.
+fn add_array(
+    dwarf: &mut DwarfUnit,
+    parent: UnitEntryId,
+    element: UnitEntryId,
+    dimensions: &[(u64, Option<u64>)],
+    attrs: Vec<(gimli::DwAt, AttributeValue)>,
+) {
+    let array = dwarf.unit.add(parent, gimli::DW_TAG_array_type);
+    let entry = dwarf.unit.get_mut(array);
+    entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(element));
+    for (name, value) in attrs {
+        entry.set(name, value);
+    }
+    for &(count, byte_stride) in dimensions {
+        let subrange = dwarf.unit.add(array, gimli::DW_TAG_subrange_type);
+        let entry = dwarf.unit.get_mut(subrange);
+        entry.set(gimli::DW_AT_count, AttributeValue::Udata(count));
+        if let Some(byte_stride) = byte_stride {
+            entry.set(gimli::DW_AT_byte_stride, AttributeValue::Udata(byte_stride));
+        }
+    }
+}
+
+#[test]
+fn test_array_strides() {
+    let output = parse_unit(|dwarf, root| {
+        let int = dwarf.unit.add(root, gimli::DW_TAG_base_type);
+        let entry = dwarf.unit.get_mut(int);
+        entry.set(gimli::DW_AT_name, AttributeValue::String(b"int".to_vec()));
+        entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(4));
+        entry.set(
+            gimli::DW_AT_encoding,
+            AttributeValue::Encoding(gimli::DW_ATE_signed),
+        );
+
+        // `int[3][4]`, laid out by the size of its elements.
+        add_array(dwarf, root, int, &[(3, None), (4, None)], Vec::new());
+        // A packed array of ten 4-bit elements, as GNAT describes a packed Ada array.
+        add_array(
+            dwarf,
+            root,
+            int,
+            &[(10, None)],
+            vec![(gimli::DW_AT_bit_stride, AttributeValue::Udata(4))],
+        );
+        // `int[3][4]` whose rows are padded to 32 bytes.
+        add_array(dwarf, root, int, &[(3, Some(32)), (4, None)], Vec::new());
+    });
+
+    let unit = &output.units[0];
+    let mut nodes = unit
+        .types
+        .values()
+        .filter(|node| node.kind == TypeKind::Array);
+    let natural = nodes.next().unwrap();
+    assert_eq!((natural.bit_stride, natural.byte_size), (None, Some(48)));
+    assert_eq!(natural.strides, [None, None]);
+    let packed = nodes.next().unwrap();
+    assert_eq!((packed.bit_stride, packed.byte_size), (Some(4), Some(5)));
+    let padded = nodes.next().unwrap();
+    assert_eq!(padded.strides, [Some(256), None]);
+    assert_eq!(padded.byte_size, Some(96));
+}
+
+// Since DWARF4, DW_AT_high_pc may be a constant, which is the offset of the end from DW_AT_low_pc
+// rather than an address.
+#[test]
+fn test_high_pc_offset() {
+    let high_pcs = [
+        (
+            "absolute",
+            AttributeValue::Address(Address::Constant(0x1040)),
+        ),
+        ("udata", AttributeValue::Udata(0x40)),
+        ("data4", AttributeValue::Data4(0x40)),
+    ];
+    let output = parse_unit(|dwarf, root| {
+        for (name, high_pc) in high_pcs.clone() {
+            let attrs = vec![
+                (
+                    gimli::DW_AT_low_pc,
+                    AttributeValue::Address(Address::Constant(0x1000)),
+                ),
+                (gimli::DW_AT_high_pc, high_pc),
+            ];
+            add_subprogram(dwarf, root, name, attrs);
+        }
+    });
+    for (name, _) in high_pcs {
+        let subprogram = &output.find_subprograms(name)[0];
+        assert_eq!(subprogram.ranges, [(0x1000, 0x1040)], "{}", name);
+    }
+}
+
+// A subprogram with DW_AT_low_pc but without DW_AT_high_pc has no ranges, and only gets its size from
+// the symbol table, while the symbols of the same name are told apart by the address of the subprogram.
+#[test]
+fn test_symbols() {
+    let mut dwarf = DwarfUnit::new(ENCODING);
+    let root = dwarf.unit.root();
+    let low_pc = |address| {
+        vec![(
+            gimli::DW_AT_low_pc,
+            AttributeValue::Address(Address::Constant(address)),
+        )]
+    };
+    add_subprogram(&mut dwarf, root, "bare", low_pc(0x10));
+    let sized = add_subprogram(&mut dwarf, root, "twin", low_pc(0x80));
+    dwarf
+        .unit
+        .get_mut(sized)
+        .set(gimli::DW_AT_high_pc, AttributeValue::Udata(0x8));
+    let symbols = [
+        ("bare", 0x10, 0x20),
+        ("twin", 0x40, 0x8),
+        ("twin", 0x80, 0x8),
+    ];
+    let output = parse_object(write_sections(&mut dwarf), &symbols);
+
+    let bare = &output.find_subprograms("bare")[0];
+    assert!(bare.ranges.is_empty());
+    let symbol = bare.symbol.as_ref().expect("bare has no symbol");
+    assert_eq!(
+        (symbol.address, symbol.size, symbol.kind.as_str()),
+        (0x10, 0x20, "Text")
+    );
+
+    let twin = &output.find_subprograms("twin")[0];
+    assert_eq!(twin.ranges, [(0x80, 0x88)]);
+    assert_eq!(
+        twin.symbol.as_ref().map(|symbol| symbol.address),
+        Some(0x80)
+    );
+}